@@ -0,0 +1,129 @@
+use super::{app_url, ExtractedLink};
+use serde::Serialize;
+
+/// An app found by `ApkMirror::search_apps` or `ApkMirror::developer_apps`,
+/// with the releases of it that were listed.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppMatch {
+    /// The name of the app, e.g. `Signal Private Messenger`.
+    pub name: String,
+    /// The slug of the developer in the URL of the app page, e.g. `signal-foundation`.
+    pub developer: String,
+    /// The slug of the app in the URL of the app page, e.g. `signal-private-messenger`.
+    pub slug: String,
+    /// The URL of the app page.
+    pub url: String,
+    /// The package ID shown on the latest release, `None` if it was not found.
+    pub package_id: Option<String>,
+    /// The version of the latest release listed by the search.
+    pub latest_version: String,
+    /// How closely the app matches the search query, from `0.0` to `1.0`,
+    /// `None` for the apps of a developer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// The releases of the app listed by the search or developer page, newest first.
+    #[serde(skip)]
+    pub releases: Vec<ExtractedLink>,
+}
+
+/// Groups releases by the app page they belong to, adding the apps not in
+/// `apps` yet in the order they are first listed, named after the title of
+/// their first release.
+pub(crate) fn group_by_app(apps: &mut Vec<AppMatch>, results: Vec<ExtractedLink>) {
+    for result in results {
+        let Some(url) = app_url(&result.link) else {
+            continue;
+        };
+        if let Some(app) = apps.iter_mut().find(|app| app.url == url) {
+            app.releases.push(result);
+            continue;
+        }
+        let mut segments = url.trim_end_matches('/').rsplit('/');
+        let slug = segments.next().unwrap_or_default().to_string();
+        let developer = segments.next().unwrap_or_default().to_string();
+        let name = result
+            .title
+            .trim()
+            .strip_suffix(result.version.as_str())
+            .unwrap_or(&result.title)
+            .trim()
+            .to_string();
+        apps.push(AppMatch {
+            name,
+            developer,
+            slug,
+            url,
+            package_id: None,
+            latest_version: result.version.clone(),
+            score: None,
+            releases: vec![result],
+        });
+    }
+}
+
+/// Scores how closely `candidate` matches `query`, from `0.0` to `1.0`, ignoring case.
+///
+/// An exact match scores `1.0`, followed by candidates starting with the query
+/// and candidates containing it. Other candidates score by the character pairs
+/// they share with the query, so that typos still rank the intended app first.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::apps::fuzzy_score;
+///
+/// assert_eq!(fuzzy_score("signal", "Signal"), 1.0);
+/// assert!(fuzzy_score("signal", "Signal Private Messenger") > fuzzy_score("signal", "Backup for Signal"));
+/// assert!(fuzzy_score("sginal", "Signal") > fuzzy_score("sginal", "Telegram"));
+/// ```
+pub fn fuzzy_score(query: &str, candidate: &str) -> f64 {
+    let query = normalize(query);
+    let candidate = normalize(candidate);
+    if query.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+    // Shorter candidates are closer to the query among those containing it
+    let closeness = query.len() as f64 / candidate.len() as f64;
+    if candidate == query {
+        1.0
+    } else if candidate.starts_with(&query) {
+        0.8 + 0.1 * closeness
+    } else if candidate.contains(&query) {
+        0.6 + 0.1 * closeness
+    } else {
+        0.6 * dice_coefficient(&query, &candidate)
+    }
+}
+
+/// Lowercases `text` and collapses runs of non-alphanumeric characters into single spaces.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the Sørensen–Dice coefficient of the character pairs of `a` and `b`.
+fn dice_coefficient(a: &str, b: &str) -> f64 {
+    let pairs = |text: &str| {
+        let chars: Vec<char> = text.chars().collect();
+        chars
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<_>>()
+    };
+    let (a, mut b) = (pairs(a), pairs(b));
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut shared = 0;
+    for pair in a {
+        if let Some(index) = b.iter().position(|other| *other == pair) {
+            b.swap_remove(index);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
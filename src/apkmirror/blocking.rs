@@ -0,0 +1,270 @@
+use super::{
+    ApkMirrorBuilder, ApkType, AppInfo, AppMatch, DownloadApkMirror, DownloadedFile, ExtractedLink,
+    FeedEntry, ReleaseInfo,
+};
+use crate::errors::DownApkError;
+use futures_util::{Stream, StreamExt};
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// A blocking version of `apkmirror::ApkMirror`.
+///
+/// Each call runs the asynchronous version to completion on a runtime owned by
+/// the instance, so no tokio runtime is needed. Like reqwest's blocking client,
+/// it must not be used from within an asynchronous runtime, as it would panic.
+///
+/// # Example
+///
+/// ```no_run
+/// use downapk::apkmirror::blocking::{single_file_download, ApkMirror};
+///
+/// fn main() {
+///     let apk_mirror = ApkMirror::new().unwrap();
+///     let results = apk_mirror.search("com.instagram.lite").unwrap();
+///     let downloads = apk_mirror.download_from_url(&results[0].link, None, Some("arm64-v8a"), None).unwrap();
+///     single_file_download(&downloads[0], "com.instagram.lite", "downloads").unwrap();
+/// }
+/// ```
+pub struct ApkMirror {
+    inner: super::ApkMirror,
+    runtime: Runtime,
+}
+
+impl ApkMirror {
+    /// Creates an instance like `apkmirror::ApkMirror::new`.
+    pub fn new() -> Result<Self, DownApkError<'static>> {
+        Self::from_builder(super::ApkMirror::builder())
+    }
+
+    /// Creates an instance with the options of `builder`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::blocking;
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// fn main() {
+    ///     let apk_mirror = blocking::ApkMirror::from_builder(ApkMirror::builder().retries(3)).unwrap();
+    /// }
+    /// ```
+    pub fn from_builder(builder: ApkMirrorBuilder) -> Result<Self, DownApkError<'static>> {
+        let runtime = runtime()?;
+        let inner = runtime.block_on(builder.build())?;
+        Ok(ApkMirror { inner, runtime })
+    }
+
+    /// Saves the session cookies, see `apkmirror::ApkMirror::save_cookies`.
+    pub fn save_cookies(&self) -> Result<(), DownApkError<'static>> {
+        self.inner.save_cookies()
+    }
+
+    /// Extracts the root links from the specified URL, see `apkmirror::ApkMirror::extract_root_links`.
+    pub fn extract_root_links(
+        &self,
+        url: &str,
+        version: Option<&str>,
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        self.block_on(self.inner.extract_root_links(url, version))
+    }
+
+    /// Searches for APKs, see `apkmirror::ApkMirror::search`.
+    pub fn search(&self, search_query: &str) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        self.block_on(self.inner.search(search_query))
+    }
+
+    /// Searches for APKs following the pages of results, see `apkmirror::ApkMirror::search_stream`.
+    ///
+    /// Each page is fetched when the iterator reaches it, so dropping the
+    /// iterator stops the search.
+    pub fn search_iter<'a>(
+        &'a self,
+        search_query: &str,
+    ) -> impl Iterator<Item = Result<ExtractedLink, DownApkError<'static>>> + 'a {
+        self.iter(self.inner.search_stream(search_query))
+    }
+
+    /// Searches for APKs like `search_iter`, stopping after `max_pages` pages of
+    /// results, see `apkmirror::ApkMirror::search_pages`.
+    pub fn search_pages_iter<'a>(
+        &'a self,
+        search_query: &str,
+        max_pages: usize,
+    ) -> impl Iterator<Item = Result<ExtractedLink, DownApkError<'static>>> + 'a {
+        self.iter(self.inner.search_pages(search_query, max_pages))
+    }
+
+    /// Searches for APKs of a version, see `apkmirror::ApkMirror::search_by_version`.
+    pub fn search_by_version(
+        &self,
+        search_query: &str,
+        version: &str,
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        self.block_on(self.inner.search_by_version(search_query, version))
+    }
+
+    /// Searches the releases of the app with the exact package ID, see
+    /// `apkmirror::ApkMirror::search_package`.
+    pub fn search_package(
+        &self,
+        package_id: &str,
+        version: Option<&str>,
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        self.block_on(self.inner.search_package(package_id, version))
+    }
+
+    /// Resolves a package ID to the URL of its app page, see `apkmirror::ApkMirror::resolve_package`.
+    pub fn resolve_package(
+        &self,
+        package_id: &str,
+    ) -> Result<Option<String>, DownApkError<'static>> {
+        self.block_on(self.inner.resolve_package(package_id))
+    }
+
+    /// Searches apps by name, see `apkmirror::ApkMirror::search_apps`.
+    pub fn search_apps(&self, name: &str) -> Result<Vec<AppMatch>, DownApkError<'static>> {
+        self.block_on(self.inner.search_apps(name))
+    }
+
+    /// Scrapes the details of an app, see `apkmirror::ApkMirror::app_info`.
+    pub fn app_info(&self, url: &str) -> Result<AppInfo, DownApkError<'static>> {
+        self.block_on(self.inner.app_info(url))
+    }
+
+    /// Scrapes the details of a release, see `apkmirror::ApkMirror::release_info`.
+    pub fn release_info(&self, url: &str) -> Result<ReleaseInfo, DownApkError<'static>> {
+        self.block_on(self.inner.release_info(url))
+    }
+
+    /// Scrapes the changes of a release, see `apkmirror::ApkMirror::whats_new`.
+    pub fn whats_new(&self, url: &str) -> Result<String, DownApkError<'static>> {
+        self.block_on(self.inner.whats_new(url))
+    }
+
+    /// Lists the apps of a developer, see `apkmirror::ApkMirror::developer_apps`.
+    pub fn developer_apps(
+        &self,
+        developer: &str,
+        max_pages: usize,
+    ) -> Result<Vec<AppMatch>, DownApkError<'static>> {
+        self.block_on(self.inner.developer_apps(developer, max_pages))
+    }
+
+    /// Reads the feed of an app, see `apkmirror::ApkMirror::app_feed`.
+    pub fn app_feed(&self, url: &str) -> Result<Vec<FeedEntry>, DownApkError<'static>> {
+        self.block_on(self.inner.app_feed(url))
+    }
+
+    /// Gets the download links of a release, see `apkmirror::ApkMirror::download_by_specifics`.
+    pub fn download_by_specifics(
+        &self,
+        url: &str,
+        apk_type: Option<ApkType>,
+        arch_: Option<&str>,
+        dpi: Option<&str>,
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
+        self.block_on(self.inner.download_by_specifics(url, apk_type, arch_, dpi))
+    }
+
+    /// Gets the download links of a release, variant or download page, see
+    /// `apkmirror::ApkMirror::download_from_url`.
+    pub fn download_from_url(
+        &self,
+        url: &str,
+        apk_type: Option<ApkType>,
+        arch_: Option<&str>,
+        dpi: Option<&str>,
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
+        self.block_on(self.inner.download_from_url(url, apk_type, arch_, dpi))
+    }
+
+    /// Downloads a file, see `apkmirror::ApkMirror::download_file`.
+    pub fn download_file(
+        &self,
+        item: &DownloadApkMirror,
+        package_name: &str,
+        output_dir: &str,
+    ) -> Result<DownloadedFile, DownApkError<'static>> {
+        self.block_on(self.inner.download_file(item, package_name, output_dir))
+    }
+
+    /// Downloads files, see `apkmirror::ApkMirror::download_files`.
+    pub fn download_files(
+        &self,
+        downlinks: &[DownloadApkMirror],
+        package_name: &str,
+        output_dir: &str,
+        concurrency: usize,
+    ) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+        self.block_on(
+            self.inner
+                .download_files(downlinks, package_name, output_dir, concurrency),
+        )
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Returns an iterator polling `stream` on the runtime of this instance.
+    fn iter<'a, T: 'a>(
+        &'a self,
+        stream: impl Stream<Item = T> + 'a,
+    ) -> impl Iterator<Item = T> + 'a {
+        let mut stream = Box::pin(stream);
+        std::iter::from_fn(move || self.block_on(stream.next()))
+    }
+}
+
+/// Downloads a file, see `apkmirror::single_file_download`.
+///
+/// Only proxies set in the environment are used, `ApkMirror::download_file`
+/// downloads through the proxy of an instance.
+pub fn single_file_download(
+    item: &DownloadApkMirror,
+    package_name: &str,
+    output_dir: &str,
+) -> Result<DownloadedFile, DownApkError<'static>> {
+    runtime()?.block_on(super::single_file_download(item, package_name, output_dir))
+}
+
+/// Downloads files one after the other, see `apkmirror::multiple_file_download`.
+///
+/// Only proxies set in the environment are used, `ApkMirror::download_files`
+/// downloads through the proxy of an instance.
+pub fn multiple_file_download(
+    downlinks: &[DownloadApkMirror],
+    package_name: &str,
+    output_dir: &str,
+) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+    runtime()?.block_on(super::multiple_file_download(
+        downlinks,
+        package_name,
+        output_dir,
+    ))
+}
+
+/// Downloads files at the same time, see `apkmirror::concurrent_file_download`.
+///
+/// Only proxies set in the environment are used, `ApkMirror::download_files`
+/// downloads through the proxy of an instance.
+pub fn concurrent_file_download(
+    downlinks: &[DownloadApkMirror],
+    package_name: &str,
+    output_dir: &str,
+    concurrency: usize,
+) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+    runtime()?.block_on(super::concurrent_file_download(
+        downlinks,
+        package_name,
+        output_dir,
+        concurrency,
+    ))
+}
+
+/// Returns a runtime running futures on the calling thread.
+fn runtime() -> Result<Runtime, DownApkError<'static>> {
+    Ok(tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?)
+}
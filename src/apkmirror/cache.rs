@@ -0,0 +1,109 @@
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Number of temporary files written by `PageCache::put`, keeping their names
+/// unique within the process.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// An on-disk cache of scraped HTML pages, keyed by URL.
+///
+/// Pages older than the time to live are fetched again. Downloaded files are
+/// never cached.
+///
+/// # Example
+///
+/// ```no_run
+/// use downapk::apkmirror::{ApkMirror, PageCache};
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let cache = PageCache::new("cache/pages", Duration::from_secs(600));
+///     let apk_mirror = ApkMirror::builder().cache(cache).build().await.unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PageCache {
+    /// The directory the pages are stored in.
+    dir: PathBuf,
+    /// How long a cached page is used before it is fetched again.
+    ttl: Duration,
+    /// Whether cached pages are ignored, fetching and storing them again.
+    refresh: bool,
+}
+
+impl PageCache {
+    /// Creates a cache storing pages in `dir` for `ttl`.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        PageCache {
+            dir: dir.into(),
+            ttl,
+            refresh: false,
+        }
+    }
+
+    /// Ignores the cached pages if `refresh` is set, fetching and storing them again.
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Returns the default directory of cached pages, `pages` in `cache_dir()`.
+    pub fn default_dir() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("pages"))
+    }
+
+    /// Returns the cached page of `url` if it is younger than the time to live.
+    pub async fn get(&self, url: &str) -> Option<String> {
+        if self.refresh {
+            return None;
+        }
+        let path = self.path(url);
+        let modified = tokio::fs::metadata(&path).await.ok()?.modified().ok()?;
+        // A modification time in the future counts as fresh
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age >= self.ttl {
+            return None;
+        }
+        tokio::fs::read_to_string(&path).await.ok()
+    }
+
+    /// Stores the page of `url`.
+    ///
+    /// The page is written to a temporary file renamed into place, so that a
+    /// concurrent run or an interrupted write never leaves a truncated page.
+    pub async fn put(&self, url: &str, html: &str) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.path(url);
+        let temp = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        if let Err(e) = tokio::fs::write(&temp, html).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e);
+        }
+        tokio::fs::rename(&temp, &path).await
+    }
+
+    /// Returns the path of the cached page of `url`.
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:x}.html", Sha256::digest(url.as_bytes())))
+    }
+}
+
+/// Returns the cache directory of downapk, under `$XDG_CACHE_HOME` or
+/// `~/.cache` if it is not set.
+pub fn cache_dir() -> Option<PathBuf> {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache_home.join("downapk"))
+}
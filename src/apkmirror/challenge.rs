@@ -0,0 +1,60 @@
+use reqwest::StatusCode;
+
+/// Markers found in Cloudflare and similar anti-bot interstitials, paired with
+/// the reason reported to the user when one of them is present in a page.
+static CHALLENGE_MARKERS: &[(&str, &str)] = &[
+    (
+        "<title>Just a moment...</title>",
+        "Cloudflare browser check",
+    ),
+    ("cf-browser-verification", "Cloudflare browser check"),
+    ("cf_chl_opt", "Cloudflare challenge"),
+    ("Attention Required! | Cloudflare", "Cloudflare block page"),
+    ("cf-error-details", "Cloudflare error page"),
+    (
+        "Checking your browser before accessing",
+        "Browser verification page",
+    ),
+    (
+        "Enable JavaScript and cookies to continue",
+        "Browser verification page",
+    ),
+];
+
+/// Checks whether a fetched page is an anti-bot challenge or interstitial
+/// instead of the page that was requested.
+///
+/// # Arguments
+///
+/// * `status` - The HTTP status code of the response.
+/// * `html` - The body of the response.
+///
+/// # Returns
+///
+/// `Some(reason)` describing the kind of challenge if one was detected, `None` otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use downapk::apkmirror::challenge::detect_challenge;
+/// use reqwest::StatusCode;
+///
+/// let html = "<html><head><title>Just a moment...</title></head></html>";
+/// assert!(detect_challenge(StatusCode::FORBIDDEN, html).is_some());
+/// assert!(detect_challenge(StatusCode::OK, "<html></html>").is_none());
+/// ```
+pub fn detect_challenge(status: StatusCode, html: &str) -> Option<&'static str> {
+    if let Some((_, reason)) = CHALLENGE_MARKERS
+        .iter()
+        .find(|(marker, _)| html.contains(marker))
+    {
+        return Some(reason);
+    }
+
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => Some("Rate limited by the server"),
+        StatusCode::FORBIDDEN => Some("Access denied by the server"),
+        StatusCode::SERVICE_UNAVAILABLE => Some("Service unavailable"),
+        _ => None,
+    }
+}
@@ -0,0 +1,115 @@
+use super::metadata::serialize_rfc3339;
+use super::ExtractedLink;
+use crate::errors::DownApkError;
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use time::OffsetDateTime;
+
+/// A release listed in the feed of an app, read by `ApkMirror::app_feed`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeedEntry {
+    /// The title of the release, e.g. `YouTube 19.02.34 by Google LLC`.
+    pub title: String,
+    /// The version of the release, read from the title, empty if it has none.
+    pub version: String,
+    /// The URL of the release page.
+    pub link: String,
+    /// When the release was published, `None` if the feed does not say or it could not be parsed.
+    #[serde(serialize_with = "serialize_rfc3339")]
+    pub published_at: Option<OffsetDateTime>,
+}
+
+impl From<FeedEntry> for ExtractedLink {
+    fn from(entry: FeedEntry) -> Self {
+        ExtractedLink {
+            title: entry.title,
+            version: entry.version,
+            link: entry.link,
+            uploaded_at: entry.published_at,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parses the releases of an RSS or Atom feed, in the order of the feed.
+///
+/// Entries without a link are skipped.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::feed::parse_feed;
+///
+/// let feed = "<rss version='2.0'><channel><item>\
+///     <title>YouTube 19.02.34 by Google LLC</title>\
+///     <link>https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/</link>\
+///     <pubDate>Fri, 05 Jan 2024 15:12:00 +0000</pubDate>\
+///     </item></channel></rss>";
+/// let entries = parse_feed(feed).unwrap();
+/// assert_eq!(entries[0].version, "19.02.34");
+/// ```
+pub fn parse_feed(xml: &str) -> Result<Vec<FeedEntry>, DownApkError<'static>> {
+    let document = Document::parse(xml).map_err(|e| format!("Could not parse feed: {}", e))?;
+
+    let entries = document
+        .descendants()
+        .filter(|node| matches!(node.tag_name().name(), "item" | "entry"))
+        .filter_map(|node| {
+            let title = child_text(node, "title").unwrap_or_default();
+            let link = entry_link(node)?;
+            let published_at = child_text(node, "pubDate")
+                .and_then(|date| OffsetDateTime::parse(&date, &Rfc2822).ok())
+                .or_else(|| {
+                    ["published", "updated"]
+                        .into_iter()
+                        .filter_map(|name| child_text(node, name))
+                        .find_map(|date| OffsetDateTime::parse(&date, &Rfc3339).ok())
+                });
+            Some(FeedEntry {
+                version: version_from_title(&title),
+                title,
+                link,
+                published_at,
+            })
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Returns the version in the title of a release, the last word starting with
+/// a digit before the ` by <developer>` suffix, e.g. `19.02.34` in
+/// `YouTube 19.02.34 beta by Google LLC`.
+fn version_from_title(title: &str) -> String {
+    let title = title.rsplit_once(" by ").map_or(title, |(title, _)| title);
+    title
+        .split_whitespace()
+        .rfind(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Returns the link of an entry, the text of an RSS `link` element or the
+/// `href` of an Atom `link` element to the alternate representation.
+fn entry_link(node: Node<'_, '_>) -> Option<String> {
+    node.children()
+        .filter(|child| child.has_tag_name("link"))
+        .find_map(|link| match link.attribute("href") {
+            Some(href) => link
+                .attribute("rel")
+                .is_none_or(|rel| rel == "alternate")
+                .then_some(href),
+            None => link.text(),
+        })
+        .map(str::trim)
+        .filter(|link| !link.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns the trimmed text of the first child element of `node` named `name`.
+fn child_text(node: Node<'_, '_>, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+}
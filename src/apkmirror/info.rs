@@ -0,0 +1,190 @@
+use super::metadata::{parse_uploaded, serialize_rfc3339};
+use super::{parse_appspecs, parse_package};
+use crate::errors::DownApkError;
+use crate::utils::selector;
+use scraper::{ElementRef, Html};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// Details of an app, scraped from its app page by `ApkMirror::app_info`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AppInfo {
+    /// The name of the app.
+    pub name: String,
+    /// The name of the developer.
+    pub developer: String,
+    /// The category of the app, e.g. `Video Players & Editors`.
+    pub category: Option<String>,
+    /// The description of the app.
+    pub description: String,
+    /// The URL of the app page.
+    pub url: String,
+}
+
+/// Details of a release, scraped from its release page and, for what the
+/// release page does not show, its first variant page by `ApkMirror::release_info`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReleaseInfo {
+    /// The title of the release, e.g. `YouTube 19.02.34`.
+    pub title: String,
+    /// The version of the release.
+    pub version: String,
+    /// The package ID of the app.
+    pub package_id: Option<String>,
+    /// The name of the developer.
+    pub developer: String,
+    /// The category of the app.
+    pub category: Option<String>,
+    /// The description of the app.
+    pub description: String,
+    /// The changes of the release, from the "What's new" section.
+    pub whats_new: String,
+    /// The permissions requested by the app.
+    pub permissions: Vec<String>,
+    /// The fingerprint of the certificate the APK is signed with, SHA-256 if
+    /// shown, SHA-1 otherwise.
+    pub signature: Option<String>,
+    /// The date and time when the release was uploaded, as shown on the page.
+    pub uploaded: String,
+    /// The UTC date and time when the release was uploaded, `None` if it could not be parsed.
+    #[serde(serialize_with = "serialize_rfc3339")]
+    pub uploaded_at: Option<OffsetDateTime>,
+    /// The URL of the release page.
+    pub url: String,
+}
+
+impl ReleaseInfo {
+    /// Fills the details missing from this release with those of `other`, such
+    /// as the details of a variant page of the release.
+    pub(crate) fn fill(&mut self, other: ReleaseInfo) {
+        let fill = |value: &mut String, other: String| {
+            if value.is_empty() {
+                *value = other;
+            }
+        };
+        fill(&mut self.title, other.title);
+        fill(&mut self.version, other.version);
+        fill(&mut self.developer, other.developer);
+        fill(&mut self.description, other.description);
+        fill(&mut self.whats_new, other.whats_new);
+        self.package_id = self.package_id.take().or(other.package_id);
+        self.category = self.category.take().or(other.category);
+        self.signature = self.signature.take().or(other.signature);
+        if self.permissions.is_empty() {
+            self.permissions = other.permissions;
+        }
+        if self.uploaded.is_empty() {
+            self.uploaded = other.uploaded;
+            self.uploaded_at = other.uploaded_at;
+        }
+    }
+
+    /// Returns whether details only shown on variant pages are missing.
+    pub(crate) fn incomplete(&self) -> bool {
+        self.package_id.is_none()
+            || self.permissions.is_empty()
+            || self.signature.is_none()
+            || self.uploaded.is_empty()
+    }
+}
+
+/// Parses the details of an app page.
+pub(crate) fn parse_app_info(url: &str, document: &Html) -> Result<AppInfo, DownApkError<'static>> {
+    Ok(AppInfo {
+        name: first_text(document, "h1")?,
+        developer: first_text(document, "h3 a")?,
+        category: Some(first_text(document, "a[href*='/categories/']")?)
+            .filter(|category| !category.is_empty()),
+        description: first_text(document, "#description .notes")?,
+        url: url.to_string(),
+    })
+}
+
+/// Parses the details shown on a release or variant page.
+pub(crate) fn parse_release_info(
+    url: &str,
+    document: &Html,
+) -> Result<ReleaseInfo, DownApkError<'static>> {
+    let app = parse_app_info(url, document)?;
+    let permission_selector = selector("#permissions li, div.permissions li")?;
+
+    let mut info = ReleaseInfo {
+        title: app.name,
+        developer: app.developer,
+        category: app.category,
+        description: app.description,
+        whats_new: parse_whats_new(document)?,
+        package_id: parse_package(document)?,
+        permissions: document
+            .select(&permission_selector)
+            .map(text)
+            .filter(|permission| !permission.is_empty())
+            .collect(),
+        signature: parse_signature(document),
+        url: url.to_string(),
+        ..Default::default()
+    };
+    for (icon, lines) in parse_appspecs(document)? {
+        match icon.as_str() {
+            "apkm-icon-file" => {
+                if let Some(version) = lines.iter().find_map(|line| line.strip_prefix("Version:")) {
+                    info.version = version
+                        .split(" (")
+                        .next()
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string();
+                }
+            }
+            "apkm-icon-calendar" => {
+                info.uploaded = lines.into_iter().next().unwrap_or_default();
+                info.uploaded_at = parse_uploaded(&info.uploaded);
+            }
+            _ => continue,
+        }
+    }
+    Ok(info)
+}
+
+/// Parses the "What's new" section of a release or variant page, empty if there is none.
+pub(crate) fn parse_whats_new(document: &Html) -> Result<String, DownApkError<'static>> {
+    first_text(document, "#whatsnew .notes")
+}
+
+/// Returns the certificate fingerprint following a `SHA-256:` label, or a
+/// `SHA-1:` label if there is none.
+fn parse_signature(document: &Html) -> Option<String> {
+    let lines: Vec<&str> = document
+        .root_element()
+        .text()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    ["SHA-256:", "SHA-1:"].into_iter().find_map(|label| {
+        let index = lines.iter().position(|line| line.starts_with(label))?;
+        let fingerprint = match lines[index][label.len()..].trim() {
+            "" => lines.get(index + 1)?,
+            fingerprint => fingerprint,
+        };
+        Some(fingerprint.to_string())
+    })
+}
+
+/// Returns the trimmed text of the first element matching `selector`, empty if there is none.
+fn first_text(document: &Html, selector_: &'static str) -> Result<String, DownApkError<'static>> {
+    Ok(document
+        .select(&selector(selector_)?)
+        .next()
+        .map(text)
+        .unwrap_or_default())
+}
+
+/// Returns the text of `element`, with each line trimmed.
+fn text(element: ElementRef<'_>) -> String {
+    element
+        .text()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,115 @@
+use serde::Serializer;
+use time::format_description::well_known::Rfc3339;
+use time::format_description::BorrowedFormatItem;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// Upload dates with a time, e.g. `January 5, 2024 at 3:12PM UTC`.
+const UPLOADED_DATE_TIME: &[BorrowedFormatItem<'_>] = format_description!(
+    "[month repr:long] [day padding:none], [year] at [hour repr:12 padding:none]:[minute][period case:upper] UTC"
+);
+
+/// Upload dates without a time, e.g. `January 5, 2024`.
+const UPLOADED_DATE: &[BorrowedFormatItem<'_>] =
+    format_description!("[month repr:long] [day padding:none], [year]");
+
+/// Parses a file size as shown by ApkMirror, e.g. `45.63 MB`, into bytes.
+///
+/// Units are powers of 1024, as on the website. A size with the exact number
+/// of bytes, e.g. `45.63 MB (47,845,123 bytes)`, yields that number.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::metadata::parse_file_size;
+///
+/// assert_eq!(parse_file_size("45.63 MB"), Some(47_846_523));
+/// assert_eq!(parse_file_size("45.63 MB (47,845,123 bytes)"), Some(47_845_123));
+/// ```
+pub fn parse_file_size(file_size: &str) -> Option<u64> {
+    let file_size = file_size.trim();
+    if let Some((_, exact)) = file_size.split_once('(') {
+        if let Some(bytes) = exact.trim_end_matches(')').trim().strip_suffix("bytes") {
+            return bytes.trim().replace(',', "").parse().ok();
+        }
+    }
+
+    let (number, unit) = file_size.split_once(' ')?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "B" | "BYTES" => 1,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        _ => return None,
+    };
+    scale(number, multiplier)
+}
+
+/// Parses a download count as shown by ApkMirror, e.g. `1.2K` or `1,234`.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::metadata::parse_downloads;
+///
+/// assert_eq!(parse_downloads("1.2K"), Some(1_200));
+/// assert_eq!(parse_downloads("1,234"), Some(1_234));
+/// ```
+pub fn parse_downloads(downloads: &str) -> Option<u64> {
+    let downloads = downloads.trim();
+    let (number, multiplier) = match downloads.char_indices().last()? {
+        (i, 'K' | 'k') => (&downloads[..i], 1_000),
+        (i, 'M' | 'm') => (&downloads[..i], 1_000_000),
+        (i, 'B' | 'b') => (&downloads[..i], 1_000_000_000),
+        _ => (downloads, 1),
+    };
+    scale(number, multiplier)
+}
+
+/// Parses an upload date as shown by ApkMirror, e.g. `January 5, 2024 at 3:12PM UTC`.
+///
+/// A date without a time yields midnight UTC.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::metadata::parse_uploaded;
+///
+/// let uploaded = parse_uploaded("January 5, 2024 at 3:12PM UTC").unwrap();
+/// assert_eq!(uploaded.unix_timestamp(), 1_704_467_520);
+/// ```
+pub fn parse_uploaded(uploaded: &str) -> Option<OffsetDateTime> {
+    let uploaded = uploaded.trim();
+    let date_time = PrimitiveDateTime::parse(uploaded, UPLOADED_DATE_TIME)
+        .or_else(|_| {
+            Date::parse(uploaded, UPLOADED_DATE).map(|date| date.with_time(Time::MIDNIGHT))
+        })
+        .ok()?;
+    Some(date_time.assume_utc())
+}
+
+/// Serializes an optional date and time as an RFC 3339 string, e.g. `2024-01-05T15:12:00Z`.
+pub(crate) fn serialize_rfc3339<S: Serializer>(
+    date_time: &Option<OffsetDateTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match date_time {
+        Some(date_time) => {
+            let date_time = date_time
+                .format(&Rfc3339)
+                .map_err(serde::ser::Error::custom)?;
+            serializer.serialize_some(&date_time)
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Multiplies a decimal number with thousands separators by `multiplier`,
+/// rounding to the nearest integer.
+fn scale(number: &str, multiplier: u64) -> Option<u64> {
+    let number: f64 = number.trim().replace(',', "").parse().ok()?;
+    if !number.is_finite() || number < 0.0 {
+        return None;
+    }
+    Some((number * multiplier as f64).round() as u64)
+}
@@ -1,27 +1,62 @@
-use crate::errors::DownApkError;
-use crate::utils::selector;
-use clap::ValueEnum;
-use console::Emoji;
+/// Apps found by searching their name
+pub mod apps;
+/// Blocking versions of `ApkMirror` and the download functions
+#[cfg(feature = "blocking")]
+pub mod blocking;
+/// Builder of `ApkMirror` instances with custom client options
+pub mod builder;
+/// On-disk cache of scraped HTML pages
+pub mod cache;
+/// Detection of anti-bot challenge pages
+pub mod challenge;
+/// RSS and Atom feeds listing the latest releases of an app
+pub mod feed;
+/// Details of apps and releases
+pub mod info;
+/// Parsing of the metadata shown for releases, such as file sizes and upload dates
+pub mod metadata;
+/// Reporting of the progress of searches and downloads
+pub mod progress;
+
+pub use apps::AppMatch;
+pub use builder::ApkMirrorBuilder;
+pub use cache::PageCache;
+pub use feed::FeedEntry;
+pub use info::{AppInfo, ReleaseInfo};
+
+use crate::errors::{ChallengePage, DownApkError};
+use crate::utils::{encode_query_value, selector};
+use apps::{fuzzy_score, group_by_app};
+use challenge::detect_challenge;
 use core::time::Duration;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::Client;
+use feed::parse_feed;
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use info::{parse_app_info, parse_release_info, parse_whats_new};
+use metadata::{parse_downloads, parse_file_size, parse_uploaded};
+use progress::{default_reporter, ProgressReporter, ProgressTask, Stage};
+use reqwest::{Client, StatusCode};
+use reqwest_cookie_store::CookieStoreMutex;
 use scraper::Html;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::cmp::min;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
-static LOOKING_GLASS: Emoji<'_, '_> = Emoji("🔍  ", "");
-static SPARKLE: Emoji<'_, '_> = Emoji("✨ ", ":-)");
-static DOWNLOAD_EMOJI: Emoji<'_, '_> = Emoji("📥 ", ":-)");
-static TRUCK: Emoji<'_, '_> = Emoji("🚚  ", "");
-
 /// Represents a structure for downloading APK files from ApkMirror.
+#[derive(Debug, Clone, Serialize)]
 pub struct DownloadApkMirror {
     /// The version of the APK file.
     pub version: String,
     /// The download link of the APK file.
     pub download_link: String,
+    /// The URL of the variant page the download link was found on.
+    pub variant_url: String,
     /// The type of the APK file. It can be either APK or BUNDLE.
     pub apk_type: ApkType,
     /// The architecture of the APK file. It can be either arm64-v8a, armeabi-v7a, x86, x86_64, universal.
@@ -33,6 +68,7 @@ pub struct DownloadApkMirror {
 }
 
 /// Represents the extracted links from a source.
+#[derive(Debug, Clone, Serialize)]
 pub struct ExtractedLink {
     /// The version of the extracted link.
     pub version: String,
@@ -42,6 +78,13 @@ pub struct ExtractedLink {
     pub file_size: String,
     /// The date and time when the link was uploaded.
     pub uploaded: String,
+    /// The number of downloads, `None` if it could not be parsed.
+    pub download_count: Option<u64>,
+    /// The file size in bytes, `None` if it could not be parsed.
+    pub file_size_bytes: Option<u64>,
+    /// The UTC date and time when the link was uploaded, `None` if it could not be parsed.
+    #[serde(serialize_with = "metadata::serialize_rfc3339")]
+    pub uploaded_at: Option<OffsetDateTime>,
     /// The actual link extracted.
     pub link: String,
     /// The title of the extracted link.
@@ -51,7 +94,7 @@ pub struct ExtractedLink {
 /// Implements the `Default` trait for the `ExtractedLinks` struct.
 ///
 /// This allows creating a default instance of `ExtractedLinks` using the `default()` method.
-/// The default instance has all fields initialized with empty strings.
+/// The default instance has all fields initialized with empty strings, and no parsed metadata.
 impl Default for ExtractedLink {
     fn default() -> Self {
         ExtractedLink {
@@ -59,6 +102,9 @@ impl Default for ExtractedLink {
             downloads: String::new(),
             file_size: String::new(),
             uploaded: String::new(),
+            download_count: None,
+            file_size_bytes: None,
+            uploaded_at: None,
             link: String::new(),
             title: String::new(),
         }
@@ -68,7 +114,9 @@ impl Default for ExtractedLink {
 /// Represents the type of APK file. This can either be a standard
 /// APK file or an Android App Bundle. Implements Display and AsRef
 /// traits to easily get the string representation.
-#[derive(PartialEq, Debug, Clone, Copy, ValueEnum)]
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "UPPERCASE")]
 pub enum ApkType {
     Bundle,
     Apk,
@@ -100,10 +148,43 @@ impl From<String> for ApkType {
     }
 }
 
+/// Represents the kind of an ApkMirror page, as detected by `page_kind`.
+#[derive(PartialEq, Debug, Clone, Copy, Serialize)]
+pub enum PageKind {
+    /// A release page listing the variants of a version.
+    Release,
+    /// A variant page with the download button of a single file.
+    Variant,
+    /// A download page holding the direct file download link.
+    Download,
+}
+
+/// Target of `ApkMirror::download_from_url`, parsed from the fetched page.
+enum UrlTarget {
+    /// The variants of a release page matching the filters.
+    Release(Vec<DownloadApkMirror>),
+    /// The file of a variant page if it matches the filters, and its download page.
+    Variant(Option<DownloadApkMirror>, Option<String>),
+}
+
+/// Represents a file downloaded to disk by `single_file_download`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadedFile {
+    /// The path the file was written to.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The lowercase hex encoded SHA-256 hash of the file.
+    pub sha256: String,
+}
+
 /// Represents an ApkMirror instance. This struct contains:
 /// - `client`: The reqwest client used to make HTTP requests.
 /// - `host`: The host URL of the ApkMirror website.
-/// - `spinner`: The progress spinner style for loading animations.
+/// - `retries`: How many times a failed page request is retried.
+/// - `cache`: The optional cache of scraped HTML pages.
+/// - `cookies`: The session cookies, optionally persisted to `cookie_file`.
+/// - `reporter`: Reports the progress of the work, with spinners by default.
 ///
 /// This is exported as part of the public API.
 pub struct ApkMirror {
@@ -111,17 +192,29 @@ pub struct ApkMirror {
     client: Client,
     /// The host of the ApkMirror instance.
     host: String,
-    /// The spinner style for loading animations.
-    spinner: ProgressStyle,
+    /// How many times a page request failing with a network error is retried.
+    retries: u32,
+    /// The cache of scraped HTML pages.
+    cache: Option<PageCache>,
+    /// The client downloading files, without the headers of page requests.
+    download_client: Client,
+    /// The cookie jar of the client.
+    cookies: Arc<CookieStoreMutex>,
+    /// The file the cookies are persisted to.
+    cookie_file: Option<PathBuf>,
+    /// Whether the cookies were restored from `cookie_file` and not checked yet.
+    session_restored: AtomicBool,
+    /// Reports the progress of the work.
+    reporter: Arc<dyn ProgressReporter>,
 }
 
 impl ApkMirror {
     /// Initializes a new ApkMirror instance with a reqwest client, host URL,
-    /// and progress spinner style.
+    /// and progress reporter.
     ///
     /// Sends a request to apkmirror.com to get valid cookies before creating
     /// the client. Configures the client with headers and enables cookie storage.
-    /// Creates a progress spinner style template. Validates that the homepage
+    /// Reports progress with spinners on the terminal. Validates that the homepage
     /// loads correctly.
     ///
     /// Returns the constructed ApkMirror instance to use for making requests.
@@ -137,53 +230,88 @@ impl ApkMirror {
     /// }
     /// ```
     pub async fn new() -> Result<Self, DownApkError<'static>> {
-        let mut headers = HeaderMap::new();
-        headers.insert(reqwest::header::ACCEPT, HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"));
-        headers.insert(
-            reqwest::header::ACCEPT_ENCODING,
-            HeaderValue::from_static("text"),
-        );
-        headers.insert(
-            reqwest::header::ACCEPT_LANGUAGE,
-            HeaderValue::from_static("en-IN,en-US;q=0.9,en;q=0.8"),
-        );
-        headers.insert(
-            reqwest::header::HOST,
-            HeaderValue::from_static("www.apkmirror.com"),
-        );
-        headers.insert("Proxy-Connection", HeaderValue::from_static("keep-alive"));
-        headers.insert(
-            reqwest::header::UPGRADE_INSECURE_REQUESTS,
-            HeaderValue::from_static("1"),
-        );
-        headers.insert(reqwest::header::USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Linux; Android 13; Pixel 5 Build/TQ3A.230901.001; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/118.0.0.0 Safari/537.36"));
-        headers.insert(
-            "X-Requested-With",
-            HeaderValue::from_static("cf.vojtechh.apkmirror"),
-        );
+        Self::builder().build().await
+    }
 
-        let client = Client::builder()
-            .cookie_store(true)
-            .default_headers(headers)
-            .build()?;
-
-        let spinner_style =
-            ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {wide_msg}")?
-                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ");
-
-        let pb = ProgressBar::new(40);
-        pb.set_style(spinner_style.clone());
-        pb.set_prefix(format!(" {} Intialise", SPARKLE));
-
-        pb.set_message("Heading to apkmirror.com for valid cookies");
-        pb.enable_steady_tick(Duration::from_millis(100));
-        let url = "https://www.apkmirror.com".to_string();
-        let res = client
-            .get(&(url.clone() + "/"))
-            .send()
-            .await?
-            .text()
-            .await?;
+    /// Downloads a file like `single_file_download`, through the proxy and
+    /// with the timeouts of this instance.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let downloads = apk_mirror.download_from_url("https://www.apkmirror.com/apk/instagram/instagram-lite/instagram-lite-390-0-0-9-116-release/", None, Some("arm64-v8a"), None).await.unwrap();
+    ///     apk_mirror.download_file(&downloads[0], "com.instagram.lite", "downloads").await.unwrap();
+    /// }
+    /// ```
+    pub async fn download_file(
+        &self,
+        item: &DownloadApkMirror,
+        package_name: &str,
+        output_dir: &str,
+    ) -> Result<DownloadedFile, DownApkError<'static>> {
+        download_file(
+            &self.download_client,
+            self.reporter.as_ref(),
+            item,
+            package_name,
+            output_dir,
+        )
+        .await
+    }
+
+    /// Downloads files like `concurrent_file_download`, through the proxy and
+    /// with the timeouts of this instance.
+    pub async fn download_files(
+        &self,
+        downlinks: &[DownloadApkMirror],
+        package_name: &str,
+        output_dir: &str,
+        concurrency: usize,
+    ) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+        download_files(
+            &self.download_client,
+            self.reporter.as_ref(),
+            downlinks,
+            package_name,
+            output_dir,
+            concurrency,
+        )
+        .await
+    }
+
+    /// Returns a builder to create an instance with custom client options.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::builder().retries(3).build().await.unwrap();
+    /// }
+    /// ```
+    pub fn builder() -> ApkMirrorBuilder {
+        ApkMirrorBuilder::new()
+    }
+
+    /// Fetches the homepage to get valid cookies and checks that it loads
+    /// correctly, then saves the cookies if there is a cookie file.
+    ///
+    /// Fails with `DownApkError::Challenge` if a challenge page was served, or
+    /// `DownApkError::Other` if the page has no search form otherwise.
+    async fn bootstrap(&self) -> Result<(), DownApkError<'static>> {
+        let pb = self.reporter.task(
+            Stage::Initialise,
+            "Heading to apkmirror.com for valid cookies",
+        );
+        let homepage = self.host.clone() + "/";
+        let res = fetch_html(&self.client, &homepage, self.retries).await?;
 
         pb.set_message("Got some cookies, parsing html");
         let document = Html::parse_document(&res);
@@ -191,15 +319,89 @@ impl ApkMirror {
         pb.set_message("Parsing html to check if page is valid");
         let selector = selector("button[class='searchButton']")?;
 
-        assert_eq!(1, document.select(&selector).count());
+        // Challenges were already detected while fetching, so this is another page
+        if document.select(&selector).count() != 1 {
+            pb.abandon("Homepage did not load correctly");
+            return Err(format!(
+                "Unexpected page at {}: the search form is missing",
+                homepage
+            )
+            .into());
+        }
 
-        pb.finish_with_message("Finished getting valid cookies");
+        pb.finish("Finished getting valid cookies");
+        // The session is only reused to save a request, failing to store it is not an error
+        let _ = self.save_cookies();
+        Ok(())
+    }
 
-        Ok(ApkMirror {
-            client,
-            host: url,
-            spinner: spinner_style,
-        })
+    /// Writes the cookies of the session to the cookie file, if there is one,
+    /// so that the next instance can skip fetching the homepage.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing nothing or an `Error` if the file could not be written.
+    pub fn save_cookies(&self) -> Result<(), DownApkError<'static>> {
+        let Some(path) = &self.cookie_file else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        let store = self
+            .cookies
+            .lock()
+            .map_err(|_| "Cookie store is poisoned")?;
+        cookie_store::serde::json::save_incl_expired_and_nonpersistent(&store, &mut writer)
+            .map_err(|e| format!("Could not save cookies to {}: {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Fetches the HTML of `url` like `fetch_html`.
+    ///
+    /// If the session was restored from the cookie file and the page is
+    /// rejected, the cookies are dropped and the homepage fetched again before
+    /// retrying once. A page is rejected when a challenge page is served,
+    /// which includes any 403 response, or on a 401 response. Only the first
+    /// rejection does so, later ones are returned as errors.
+    async fn fetch(&self, url: &str) -> Result<String, DownApkError<'static>> {
+        match fetch_html(&self.client, url, self.retries).await {
+            Err(e)
+                if rejected_session(&e)
+                    && self.session_restored.swap(false, AtomicOrdering::SeqCst) =>
+            {
+                if let Ok(mut store) = self.cookies.lock() {
+                    store.clear();
+                }
+                self.bootstrap().await?;
+                fetch_html(&self.client, url, self.retries).await
+            }
+            result => result,
+        }
+    }
+
+    /// Fetches the HTML of a scraped page like `fetch`, going through the
+    /// page cache if there is one.
+    async fn fetch_page(&self, url: &str) -> Result<String, DownApkError<'static>> {
+        let Some(cache) = &self.cache else {
+            return self.fetch(url).await;
+        };
+        if let Some(html) = cache.get(url).await {
+            return Ok(html);
+        }
+        let html = self.fetch(url).await?;
+        // The cache only saves requests, failing to write it is not an error
+        let _ = cache.put(url, &html).await;
+        Ok(html)
+    }
+
+    /// Returns the URL of the first page of results of a search for `search_query`.
+    fn search_url(&self, search_query: &str) -> String {
+        self.absolute_url(&format!(
+            "/?post_type=app_release&searchtype=apk&s={}",
+            encode_query_value(search_query)
+        ))
     }
 
     /// Constructs an absolute URL by prepending the host if the provided
@@ -241,20 +443,26 @@ impl ApkMirror {
         &self,
         url: &str,
         version: Option<&str>,
-    ) -> Result<Vec<ExtractedLink>, DownApkError> {
-        let pb = ProgressBar::new(40);
-        pb.set_style(self.spinner.clone());
-        pb.set_prefix(format!(" {} Search", LOOKING_GLASS));
-        match version {
-            Some(version) => {
-                pb.set_message(format!("Searching in {} for version {}", url, version))
-            }
-            None => pb.set_message(format!("Searching in {}", url)),
-        }
-        pb.enable_steady_tick(Duration::from_millis(100));
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        let (results, _) = self.extract_root_links_page(url, version).await?;
+        Ok(results)
+    }
+
+    /// Extracts the root links of a single page of results, along with the URL
+    /// of the next page if the results are paginated.
+    async fn extract_root_links_page(
+        &self,
+        url: &str,
+        version: Option<&str>,
+    ) -> Result<(Vec<ExtractedLink>, Option<String>), DownApkError<'static>> {
+        let message = match version {
+            Some(version) => format!("Searching in {} for version {}", url, version),
+            None => format!("Searching in {}", url),
+        };
+        let pb = self.reporter.task(Stage::Search, &message);
 
-        pb.set_message(format!("Making request to {}", url));
-        let res = self.client.get(url).send().await?.text().await?;
+        pb.set_message(&format!("Making request to {}", url));
+        let res = self.fetch_page(url).await?;
 
         pb.set_message("Parsing html");
         let document = Html::parse_document(&res);
@@ -281,12 +489,8 @@ impl ApkMirror {
                     None => continue,
                 };
 
-                let link = match link {
-                    Some(link) => self.absolute_url(
-                        link.value()
-                            .attr("href")
-                            .expect("Could not get attribute href"),
-                    ),
+                let link = match link.and_then(|link| link.value().attr("href")) {
+                    Some(href) => self.absolute_url(href),
                     None => continue,
                 };
 
@@ -302,8 +506,7 @@ impl ApkMirror {
                                         .text()
                                         .collect::<String>()
                                         .trim()
-                                        .strip_suffix(':')
-                                        .expect("Could not strip suffix")
+                                        .trim_end_matches(':')
                                         .to_owned();
                                     name
                                 }
@@ -317,9 +520,18 @@ impl ApkMirror {
 
                             match name.as_str() {
                                 "Version" => extracted_link.version = value,
-                                "Downloads" => extracted_link.downloads = value,
-                                "File Size" => extracted_link.file_size = value,
-                                "Uploaded" => extracted_link.uploaded = value,
+                                "Downloads" => {
+                                    extracted_link.download_count = parse_downloads(&value);
+                                    extracted_link.downloads = value;
+                                }
+                                "File Size" => {
+                                    extracted_link.file_size_bytes = parse_file_size(&value);
+                                    extracted_link.file_size = value;
+                                }
+                                "Uploaded" => {
+                                    extracted_link.uploaded_at = parse_uploaded(&value);
+                                    extracted_link.uploaded = value;
+                                }
                                 _ => continue,
                             }
                         }
@@ -339,9 +551,39 @@ impl ApkMirror {
                 results.push(extracted_link);
             }
         }
-        pb.finish_with_message("Finished search");
+        let next_page_selector = selector("a.nextpostslink")?;
+        let next_page = document
+            .select(&next_page_selector)
+            .next()
+            .and_then(|link| link.value().attr("href"))
+            .map(|href| self.absolute_url(href))
+            .filter(|next_page| next_page != url);
+        pb.finish("Finished search");
+
+        Ok((results, next_page))
+    }
 
-        Ok(results)
+    /// Streams the root links from the specified URL and up to `max_pages - 1`
+    /// pages following it, fetching each page only once the results of the
+    /// previous one were consumed.
+    fn root_links_stream<'a>(
+        &'a self,
+        url: String,
+        version: Option<&'a str>,
+        max_pages: usize,
+    ) -> impl Stream<Item = Result<ExtractedLink, DownApkError<'static>>> + 'a {
+        stream::try_unfold(
+            (Some(url), max_pages),
+            move |(url, pages_left)| async move {
+                let (Some(url), 1..) = (url, pages_left) else {
+                    return Ok::<_, DownApkError<'static>>(None);
+                };
+                let (results, next_page) = self.extract_root_links_page(&url, version).await?;
+                let results = stream::iter(results.into_iter().map(Ok));
+                Ok(Some((results, (next_page, pages_left - 1))))
+            },
+        )
+        .try_flatten()
     }
 
     /// Searches for APKs on ApkMirror based on the specified search query.
@@ -365,15 +607,81 @@ impl ApkMirror {
     ///     let results = apk_mirror.search("com.instagram.lite").await;
     /// }
     /// ```
-    pub async fn search(&self, search_query: &str) -> Result<Vec<ExtractedLink>, DownApkError> {
-        let url = self.absolute_url(&format!(
-            "/?post_type=app_release&searchtype=apk&s={}",
-            search_query
-        ));
+    pub async fn search(
+        &self,
+        search_query: &str,
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        let url = self.search_url(search_query);
 
         self.extract_root_links(&url, None).await
     }
 
+    /// Searches for APKs on ApkMirror based on the specified search query,
+    /// following the pages of results.
+    ///
+    /// Results are yielded as each page arrives, and the next page is only
+    /// fetched once the results of the previous one were consumed, so dropping
+    /// the stream stops the search.
+    ///
+    /// # Arguments
+    ///
+    /// * `search_query` - The search query to use.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of `ExtractedLink`s, ending with an `Error` if a page could not be fetched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    /// use futures_util::{pin_mut, TryStreamExt};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let results = apk_mirror.search_stream("com.instagram.lite");
+    ///     pin_mut!(results);
+    ///     while let Some(link) = results.try_next().await.unwrap() {
+    ///         if link.version == "390.0.0.9.116" {
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn search_stream<'a>(
+        &'a self,
+        search_query: &str,
+    ) -> impl Stream<Item = Result<ExtractedLink, DownApkError<'static>>> + 'a {
+        let url = self.search_url(search_query);
+
+        self.root_links_stream(url, None, usize::MAX)
+    }
+
+    /// Searches for APKs like `search_stream`, stopping after `max_pages` pages of results.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    /// use futures_util::TryStreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let results: Vec<_> = apk_mirror.search_pages("com.instagram.lite", 3).try_collect().await.unwrap();
+    /// }
+    /// ```
+    pub fn search_pages<'a>(
+        &'a self,
+        search_query: &str,
+        max_pages: usize,
+    ) -> impl Stream<Item = Result<ExtractedLink, DownApkError<'static>>> + 'a {
+        let url = self.search_url(search_query);
+
+        self.root_links_stream(url, None, max_pages)
+    }
+
     /// Searches for APKs on ApkMirror based on the specified search query and version.
     ///
     /// # Arguments
@@ -400,15 +708,404 @@ impl ApkMirror {
         &self,
         search_query: &str,
         version: &str,
-    ) -> Result<Vec<ExtractedLink>, DownApkError> {
-        let url = self.absolute_url(&format!(
-            "/?post_type=app_release&searchtype=apk&s={}",
-            search_query
-        ));
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        let url = self.search_url(search_query);
 
         self.extract_root_links(&url, Some(version)).await
     }
 
+    /// Searches the releases of the app with the exact package ID, leaving out
+    /// the other apps matching the search query, such as YouTube Music when
+    /// searching for `com.google.android.youtube`.
+    ///
+    /// # Arguments
+    ///
+    /// * `package_id` - The package ID of the app.
+    /// * `version` - Optional version to filter the results by.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the releases of the app, empty if no app with the
+    /// package ID was found, or an `Error` if the search fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let results = apk_mirror.search_package("com.google.android.youtube", None).await.unwrap();
+    /// }
+    /// ```
+    pub async fn search_package(
+        &self,
+        package_id: &str,
+        version: Option<&str>,
+    ) -> Result<Vec<ExtractedLink>, DownApkError<'static>> {
+        let mut results = match version {
+            Some(version) => self.search_by_version(package_id, version).await?,
+            None => self.search(package_id).await?,
+        };
+        match self.resolve_package_in(package_id, &results).await? {
+            Some(app) => results.retain(|result| app_url(&result.link).as_ref() == Some(&app)),
+            None => results.clear(),
+        }
+        Ok(results)
+    }
+
+    /// Searches apps by name, grouping the releases found by app and ranking
+    /// the apps by how closely their name, slug or package ID matches `name`.
+    ///
+    /// The package ID of each app is read from its latest release, costing a
+    /// request or two per app, `PACKAGE_LOOKUPS` of them at the same time.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the apps, best match first, or an `Error` if the search fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let apps = apk_mirror.search_apps("Signal").await.unwrap();
+    ///     let releases = &apps[0].releases;
+    /// }
+    /// ```
+    pub async fn search_apps(&self, name: &str) -> Result<Vec<AppMatch>, DownApkError<'static>> {
+        let mut apps = vec![];
+        group_by_app(&mut apps, self.search(name).await?);
+
+        let pb = self
+            .reporter
+            .task(Stage::Search, &format!("Ranking apps matching {}", name));
+        pb.set_message(&format!("Reading the packages of {} apps", apps.len()));
+        self.read_packages(&mut apps).await?;
+        for app in &mut apps {
+            let score = [Some(&app.name), Some(&app.slug), app.package_id.as_ref()]
+                .into_iter()
+                .flatten()
+                .map(|candidate| fuzzy_score(name, candidate))
+                .fold(0.0, f64::max);
+            app.score = Some(score);
+        }
+        // Stable, so that equally close apps keep the order of the website
+        apps.sort_by(|a, b| {
+            let score = |app: &AppMatch| app.score.unwrap_or_default();
+            score(b).total_cmp(&score(a))
+        });
+        pb.finish(&format!("Found {} apps matching {}", apps.len(), name));
+        Ok(apps)
+    }
+
+    /// Lists the apps of a developer by crawling the uploads listed on the
+    /// developer page and grouping them by app.
+    ///
+    /// The crawl stops after `max_pages` pages, or once `DEVELOPER_PAGES_WITHOUT_NEW_APPS`
+    /// pages in a row only listed apps that were already found, so an app
+    /// whose latest upload is older than that may be left out. The package ID
+    /// of each app is read from its latest release.
+    ///
+    /// # Arguments
+    ///
+    /// * `developer` - The slug of the developer, e.g. `google-inc`, or the URL
+    ///   of the developer page or of any page of one of their apps.
+    /// * `max_pages` - How many pages of uploads are read at most.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the apps, most recently updated first, or an
+    /// `Error` if `developer` is not a slug or URL of a developer or a page
+    /// could not be fetched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let apps = apk_mirror.developer_apps("signal", 10).await.unwrap();
+    ///     for app in apps {
+    ///         println!("{:?} {}", app.package_id, app.latest_version);
+    ///     }
+    /// }
+    /// ```
+    pub async fn developer_apps(
+        &self,
+        developer: &str,
+        max_pages: usize,
+    ) -> Result<Vec<AppMatch>, DownApkError<'static>> {
+        let slug = developer_slug(developer)
+            .ok_or_else(|| format!("{} is not a developer slug or URL", developer))?;
+
+        let mut apps = vec![];
+        let mut next_page = Some(self.absolute_url(&format!("/apk/{}/", slug)));
+        let mut pages_without_new_apps = 0;
+        for _ in 0..max_pages {
+            let Some(url) = next_page.take() else {
+                break;
+            };
+            let (results, next) = self.extract_root_links_page(&url, None).await?;
+            let known = apps.len();
+            group_by_app(&mut apps, results);
+            pages_without_new_apps = match apps.len() == known {
+                true => pages_without_new_apps + 1,
+                false => 0,
+            };
+            if pages_without_new_apps == DEVELOPER_PAGES_WITHOUT_NEW_APPS {
+                break;
+            }
+            next_page = next;
+        }
+
+        let pb = self
+            .reporter
+            .task(Stage::Search, &format!("Listing the apps of {}", slug));
+        pb.set_message(&format!("Reading the packages of {} apps", apps.len()));
+        self.read_packages(&mut apps).await?;
+        pb.finish(&format!("Found {} apps of {}", apps.len(), slug));
+        Ok(apps)
+    }
+
+    /// Resolves a package ID to the URL of its app page, e.g.
+    /// `https://www.apkmirror.com/apk/google-inc/youtube/` for `com.google.android.youtube`.
+    ///
+    /// The apps found by searching the package ID are checked in order, by
+    /// reading the package ID shown on one of their releases.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the URL of the app page, `None` if no app has the
+    /// package ID, or an `Error` if a page could not be fetched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let app = apk_mirror.resolve_package("com.google.android.youtube").await.unwrap();
+    /// }
+    /// ```
+    pub async fn resolve_package(
+        &self,
+        package_id: &str,
+    ) -> Result<Option<String>, DownApkError<'static>> {
+        let results = self.search(package_id).await?;
+        self.resolve_package_in(package_id, &results).await
+    }
+
+    /// Returns the app page among those of `results` whose releases have the package ID.
+    async fn resolve_package_in(
+        &self,
+        package_id: &str,
+        results: &[ExtractedLink],
+    ) -> Result<Option<String>, DownApkError<'static>> {
+        let pb = self
+            .reporter
+            .task(Stage::Search, &format!("Resolving package {}", package_id));
+
+        let mut checked = vec![];
+        for result in results {
+            let Some(app) = app_url(&result.link) else {
+                continue;
+            };
+            if checked.contains(&app) {
+                continue;
+            }
+            pb.set_message(&format!("Checking the package of {}", app));
+            if self.release_package(&result.link).await?.as_deref() == Some(package_id) {
+                pb.finish(&format!("Resolved package {} to {}", package_id, app));
+                return Ok(Some(app));
+            }
+            checked.push(app);
+        }
+        pb.abandon(&format!("No app found with package {}", package_id));
+        Ok(None)
+    }
+
+    /// Scrapes the details of an app from its app page.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the app page, or of a release, variant or download page of the app.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let app = apk_mirror.app_info("https://www.apkmirror.com/apk/google-inc/youtube/").await.unwrap();
+    ///     println!("{} by {}", app.name, app.developer);
+    /// }
+    /// ```
+    pub async fn app_info(&self, url: &str) -> Result<AppInfo, DownApkError<'static>> {
+        let url = app_url(url).unwrap_or_else(|| url.to_string());
+        let document = Html::parse_document(&self.fetch_page(&url).await?);
+        parse_app_info(&url, &document)
+    }
+
+    /// Scrapes the details of a release from its release page, reading those
+    /// only shown on variant pages, such as the permissions and signature, from
+    /// its first variant page.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the release page.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let release = apk_mirror.release_info("https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/").await.unwrap();
+    ///     println!("{}", release.whats_new);
+    /// }
+    /// ```
+    pub async fn release_info(&self, url: &str) -> Result<ReleaseInfo, DownApkError<'static>> {
+        let variant_link_selector = selector("div.table-row a.accent_color")?;
+
+        let (mut info, variant_url) = {
+            let document = Html::parse_document(&self.fetch_page(url).await?);
+            let variant_url = document
+                .select(&variant_link_selector)
+                .next()
+                .and_then(|link| link.value().attr("href"))
+                .map(|href| self.absolute_url(href));
+            (parse_release_info(url, &document)?, variant_url)
+        };
+        if let (true, Some(variant_url)) = (info.incomplete(), variant_url) {
+            let document = Html::parse_document(&self.fetch_page(&variant_url).await?);
+            info.fill(parse_release_info(&variant_url, &document)?);
+        }
+        Ok(info)
+    }
+
+    /// Reads the feed of an app, listing its latest releases in a single
+    /// request much lighter than a search.
+    ///
+    /// The feed is fetched on every call instead of going through the page
+    /// cache, so that new uploads show up right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the app page, or of a release, variant or download page of the app.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the releases listed by the feed, newest first, or
+    /// an `Error` if the feed could not be fetched or parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let releases = apk_mirror.app_feed("https://www.apkmirror.com/apk/google-inc/youtube/").await.unwrap();
+    ///     println!("Latest version: {}", releases[0].version);
+    /// }
+    /// ```
+    pub async fn app_feed(&self, url: &str) -> Result<Vec<FeedEntry>, DownApkError<'static>> {
+        let app = app_url(url).ok_or_else(|| format!("{} is not a page of an app", url))?;
+        let url = format!("{}feed/", app);
+        let pb = self
+            .reporter
+            .task(Stage::Search, &format!("Reading the feed of {}", app));
+        let entries = parse_feed(&self.fetch(&url).await?)?;
+        pb.finish(&format!(
+            "Found {} releases in the feed of {}",
+            entries.len(),
+            app
+        ));
+        Ok(entries)
+    }
+
+    /// Scrapes the changes of a release from the "What's new" section of its
+    /// release page, one change per line.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the release page.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the changes, empty if the release page does not
+    /// list any, or an `Error` if the page could not be fetched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let changes = apk_mirror.whats_new("https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/").await.unwrap();
+    /// }
+    /// ```
+    pub async fn whats_new(&self, url: &str) -> Result<String, DownApkError<'static>> {
+        let document = Html::parse_document(&self.fetch_page(url).await?);
+        parse_whats_new(&document)
+    }
+
+    /// Sets the package ID of each app to the one shown on its latest release,
+    /// reading up to `PACKAGE_LOOKUPS` releases at the same time.
+    async fn read_packages(&self, apps: &mut [AppMatch]) -> Result<(), DownApkError<'static>> {
+        let packages: Vec<_> = stream::iter(apps.iter())
+            .map(|app| self.release_package(&app.releases[0].link))
+            .buffered(PACKAGE_LOOKUPS)
+            .try_collect()
+            .await?;
+        for (app, package_id) in apps.iter_mut().zip(packages) {
+            app.package_id = package_id;
+        }
+        Ok(())
+    }
+
+    /// Returns the package ID shown on a release page, or on its first variant
+    /// page if the release page does not show it.
+    async fn release_package(
+        &self,
+        release_url: &str,
+    ) -> Result<Option<String>, DownApkError<'static>> {
+        let variant_link_selector = selector("div.table-row a.accent_color")?;
+
+        let variant_url = {
+            let document = Html::parse_document(&self.fetch_page(release_url).await?);
+            if let Some(package) = parse_package(&document)? {
+                return Ok(Some(package));
+            }
+            document
+                .select(&variant_link_selector)
+                .next()
+                .and_then(|link| link.value().attr("href"))
+                .map(|href| self.absolute_url(href))
+        };
+        let Some(variant_url) = variant_url else {
+            return Ok(None);
+        };
+        let document = Html::parse_document(&self.fetch_page(&variant_url).await?);
+        parse_package(&document)
+    }
+
     /// Downloads APKs from ApkMirror based on the specified URL and optional parameters.
     ///
     /// # Arguments
@@ -439,16 +1136,134 @@ impl ApkMirror {
         apk_type: Option<ApkType>,
         arch_: Option<&str>,
         dpi: Option<&str>,
-    ) -> Result<Vec<DownloadApkMirror>, DownApkError> {
-        let pb = ProgressBar::new(40);
-        pb.set_style(self.spinner.clone());
-        pb.set_prefix(format!(" {} Get file download links", TRUCK));
-        pb.set_message(format!("Trying to get all download links from {}", url));
-        pb.enable_steady_tick(Duration::from_millis(100));
-        let res = self.client.get(url).send().await?.text().await?;
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
+        let pb = self.reporter.task(
+            Stage::DownloadLinks,
+            &format!("Trying to get all download links from {}", url),
+        );
+        let res = self.fetch_page(url).await?;
+
+        let variants = self.parse_variants(
+            &Html::parse_document(&res),
+            apk_type,
+            arch_,
+            dpi,
+            pb.as_ref(),
+        )?;
+        let results = self.resolve_download_links(variants, pb.as_ref()).await?;
+
+        pb.finish("Finished getting all download links");
+        Ok(results)
+    }
 
-        let document = Html::parse_document(&res);
+    /// Downloads APKs from ApkMirror based on the URL of a release, variant or
+    /// download page and optional parameters.
+    ///
+    /// The kind of page is detected from its content. A release page is handled
+    /// like `download_by_specifics`, while a variant or download page yields the
+    /// single file it offers, provided it matches the optional parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of a release, variant or download page.
+    /// * `apk_type` - Optional type of the APK (e.g., arm64-v8a).
+    /// * `arch_` - Optional architecture of the APK (e.g., arm64).
+    /// * `dpi` - Optional DPI (dots per inch) of the APK.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of `DownloadApkMirror` or an `Error` if the page is not
+    /// one of the supported kinds or the download fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use downapk::apkmirror::ApkMirror;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let apk_mirror = ApkMirror::new().await.unwrap();
+    ///     let downloads = apk_mirror.download_from_url("https://www.apkmirror.com/apk/instagram/instagram-lite/instagram-lite-390-0-0-9-116-release/instagram-lite-390-0-0-9-116-android-apk-download/", None, None, None).await;
+    /// }
+    /// ```
+    pub async fn download_from_url(
+        &self,
+        url: &str,
+        apk_type: Option<ApkType>,
+        arch_: Option<&str>,
+        dpi: Option<&str>,
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
+        let pb = self.reporter.task(
+            Stage::DownloadLinks,
+            &format!("Detecting the kind of page at {}", url),
+        );
+
+        let mut url = url.to_string();
+        let mut res = self.fetch_page(&url).await?;
+        if page_kind(&Html::parse_document(&res))? == Some(PageKind::Download) {
+            // The file details are only shown on the variant page the download page belongs to
+            url = variant_url_from_download_url(&url)
+                .ok_or_else(|| format!("Could not find the variant page of {}", url))?;
+            pb.set_message(&format!("Heading to variant page {}", url));
+            res = self.fetch_page(&url).await?;
+        }
 
+        let target = {
+            let document = Html::parse_document(&res);
+            match page_kind(&document)? {
+                Some(PageKind::Release) => {
+                    pb.set_message("Found a release page, processing each link");
+                    UrlTarget::Release(self.parse_variants(
+                        &document,
+                        apk_type,
+                        arch_,
+                        dpi,
+                        pb.as_ref(),
+                    )?)
+                }
+                Some(PageKind::Variant) => {
+                    pb.set_message("Found a variant page");
+                    UrlTarget::Variant(
+                        parse_variant_page(&url, &document, apk_type, arch_, dpi)?,
+                        self.parse_download_button(&document)?,
+                    )
+                }
+                _ => Err(format!(
+                    "{} is not a release, variant or download page",
+                    url
+                ))?,
+            }
+        };
+
+        let results = match target {
+            UrlTarget::Release(variants) => {
+                self.resolve_download_links(variants, pb.as_ref()).await?
+            }
+            UrlTarget::Variant(Some(mut item), Some(download_page)) => {
+                item.download_link = self
+                    .final_download_link(&download_page, pb.as_ref())
+                    .await?;
+                vec![item]
+            }
+            UrlTarget::Variant(Some(_), None) => Err(DownApkError::from("No download link found"))?,
+            UrlTarget::Variant(None, _) => vec![],
+        };
+
+        pb.finish("Finished getting all download links");
+        Ok(results)
+    }
+
+    /// Parses the variants table of a release page, keeping the rows matching
+    /// the optional parameters. The `download_link` of each returned item is
+    /// still its variant page URL, see `resolve_download_links`.
+    fn parse_variants(
+        &self,
+        document: &Html,
+        apk_type: Option<ApkType>,
+        arch_: Option<&str>,
+        dpi: Option<&str>,
+        pb: &dyn ProgressTask,
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
         let table_row_selector = selector("div[class='table-row headerFont']")?;
         let table_head_selector =
             selector("div[class='table-cell rowheight addseparator expand pad dowrap']")?;
@@ -483,67 +1298,51 @@ impl ApkMirror {
 
                 let version = anchor_elem.text().collect::<String>().trim().to_string();
 
-                let download_link = self.absolute_url(
-                    anchor_elem
-                        .value()
-                        .attr("href")
-                        .expect("Could not get attribute href"),
-                );
+                let variant_url = match anchor_elem.value().attr("href") {
+                    Some(href) => self.absolute_url(href),
+                    None => continue,
+                };
 
-                if !badge_text.is_empty() && !version.is_empty() && !download_link.is_empty() {
+                if !badge_text.is_empty() && !version.is_empty() && !variant_url.is_empty() {
                     if let Some(apk_type) = apk_type {
                         if apk_type != badge {
-                            pb.set_message(format!("Skipping type {}", badge_text));
+                            pb.set_message(&format!("Skipping type {}", badge_text));
                             continue;
                         }
                     }
                     let arch: String = table_row_element
                         .select(metadata_selector)
                         .nth(1)
-                        .expect("Could not get arch string")
-                        .text()
-                        .collect::<String>()
-                        .trim()
-                        .to_string();
+                        .map(|element| element.text().collect::<String>().trim().to_string())
+                        .unwrap_or_default();
                     if let Some(arch_) = arch_ {
                         if arch_ != arch {
-                            pb.set_message(format!("Skipping arch {}", arch));
+                            pb.set_message(&format!("Skipping arch {}", arch));
                             continue;
                         }
                     }
                     let screen_dpi = table_row_element
                         .select(metadata_selector)
                         .nth(3)
-                        .expect("Could not get screen dpi")
-                        .text()
-                        .collect::<String>()
-                        .trim()
-                        .to_string();
+                        .map(|element| element.text().collect::<String>().trim().to_string())
+                        .unwrap_or_default();
                     if let Some(dpi) = dpi {
                         if dpi != screen_dpi {
-                            pb.set_message(format!("Skipping dpi {}", screen_dpi));
+                            pb.set_message(&format!("Skipping dpi {}", screen_dpi));
                             continue;
                         }
                     }
                     let min_version = table_row_element
                         .select(metadata_selector)
                         .nth(2)
-                        .expect("Could not get min version")
-                        .text()
-                        .collect::<String>()
-                        .trim()
-                        .to_string();
-                    pb.set_message(format!("Found version: {} with type: {} and arch: {} and min_version: {} and screen_dpi: {}", version, badge_text, arch, min_version, screen_dpi));
+                        .map(|element| element.text().collect::<String>().trim().to_string())
+                        .unwrap_or_default();
+                    pb.set_message(&format!("Found version: {} with type: {} and arch: {} and min_version: {} and screen_dpi: {}", version, badge_text, arch, min_version, screen_dpi));
                     results.push(DownloadApkMirror {
                         version,
-                        download_link: match self.download_link(&download_link, &pb).await {
-                            Ok(download_link) => download_link,
-                            Err(_) => {
-                                println!("Could not get download link for {}", download_link);
-                                continue;
-                            }
-                        },
-                        apk_type: ApkType::from(badge_text),
+                        download_link: variant_url.clone(),
+                        variant_url,
+                        apk_type: badge,
                         arch,
                         min_version,
                         screen_dpi,
@@ -551,7 +1350,36 @@ impl ApkMirror {
                 }
             }
         }
-        pb.finish_with_message("Finished getting all download links");
+        Ok(results)
+    }
+
+    /// Replaces the variant page URL in the `download_link` of each item by the
+    /// final direct file download link. Items whose link could not be found are
+    /// skipped, while a challenge page aborts.
+    async fn resolve_download_links(
+        &self,
+        variants: Vec<DownloadApkMirror>,
+        pb: &dyn ProgressTask,
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
+        let mut results = Vec::with_capacity(variants.len());
+        for mut item in variants {
+            match self.download_link(&item.variant_url, pb).await {
+                Ok(download_link) => {
+                    item.download_link = download_link;
+                    results.push(item);
+                }
+                Err(e @ DownApkError::Challenge(_)) => {
+                    pb.abandon("Blocked by a challenge page");
+                    return Err(e);
+                }
+                Err(_) => {
+                    pb.println(&format!(
+                        "Could not get download link for {}",
+                        item.variant_url
+                    ));
+                }
+            }
+        }
         Ok(results)
     }
 
@@ -561,7 +1389,7 @@ impl ApkMirror {
         &self,
         url: &str,
         arch: Option<&str>,
-    ) -> Result<Vec<DownloadApkMirror>, DownApkError> {
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
         self.download_by_specifics(url, None, arch, None).await
     }
 
@@ -571,7 +1399,7 @@ impl ApkMirror {
         &self,
         url: &str,
         apk_type: Option<ApkType>,
-    ) -> Result<Vec<DownloadApkMirror>, DownApkError> {
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
         self.download_by_specifics(url, apk_type, None, None).await
     }
 
@@ -581,13 +1409,16 @@ impl ApkMirror {
         &self,
         url: &str,
         dpi: Option<&str>,
-    ) -> Result<Vec<DownloadApkMirror>, DownApkError> {
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
         self.download_by_specifics(url, None, None, dpi).await
     }
 
     /// Gets the download link of the specified URL without any specific parameters.
     /// This method is a shorthand for `download_by_specifics(url, None, None, None)`.
-    pub async fn _download(&self, url: &str) -> Result<Vec<DownloadApkMirror>, DownApkError> {
+    pub async fn _download(
+        &self,
+        url: &str,
+    ) -> Result<Vec<DownloadApkMirror>, DownApkError<'static>> {
         self.download_by_specifics(url, None, None, None).await
     }
 
@@ -603,53 +1434,344 @@ impl ApkMirror {
     /// # Returns
     ///
     /// A `Result` containing the final download link or an `Error` if the download link could not be found.
-    async fn download_link(&self, url: &str, pb: &ProgressBar) -> Result<String, DownApkError> {
-        pb.set_message(format!("Trying to get download page link from {}", url));
-        let res = self.client.get(url).send().await?.text().await?;
+    async fn download_link(
+        &self,
+        url: &str,
+        pb: &dyn ProgressTask,
+    ) -> Result<String, DownApkError<'static>> {
+        pb.set_message(&format!("Trying to get download page link from {}", url));
+        let res = self.fetch_page(url).await?;
 
-        let document = Html::parse_document(&res);
+        let download_page = self
+            .parse_download_button(&Html::parse_document(&res))?
+            .ok_or("No download link found")?;
+
+        self.final_download_link(&download_page, pb).await
+    }
+
+    /// Gets the final direct file download link from the download page at `url`.
+    async fn final_download_link(
+        &self,
+        url: &str,
+        pb: &dyn ProgressTask,
+    ) -> Result<String, DownApkError<'static>> {
+        pb.set_message("Found download link page, trying to get final download link");
+        let res = self.fetch(url).await?;
 
-        let download_button_selector = selector("a.accent_bg.btn.btn-flat.downloadButton")?;
         let final_download_link_selector =
             selector("a[rel='nofollow'][data-google-vignette='false']")?;
+        let final_download_link = Html::parse_document(&res)
+            .select(&final_download_link_selector)
+            .next()
+            .map(|link| {
+                link.value()
+                    .attr("href")
+                    .map(|href| self.absolute_url(href))
+            });
+
+        let final_download_link = match final_download_link {
+            Some(Some(final_download_link)) => final_download_link,
+            Some(None) => Err(DownApkError::from("Final download link has no href"))?,
+            None => Err(DownApkError::from("No final download link found"))?,
+        };
+        pb.set_message(&format!(
+            "Found final download link: {}",
+            final_download_link
+        ));
+        pb.set_message("Finished getting download link");
+        Ok(final_download_link)
+    }
+
+    /// Returns the absolute URL of the download page linked by the download
+    /// button of a variant page, if there is one.
+    fn parse_download_button(
+        &self,
+        document: &Html,
+    ) -> Result<Option<String>, DownApkError<'static>> {
+        let download_button_selector = selector("a.accent_bg.btn.btn-flat.downloadButton")?;
+
+        match document.select(&download_button_selector).next() {
+            Some(download_button) => match download_button.value().attr("href") {
+                Some(href) => Ok(Some(self.absolute_url(href))),
+                None => Err(DownApkError::from("Download link has no href")),
+            },
+            None => Ok(None),
+        }
+    }
+
+    // ... other methods here ...
+}
+
+/// Detects the kind of an ApkMirror page from its content.
+///
+/// # Returns
+///
+/// A `Result` containing the `PageKind`, or `None` if the page is none of the known kinds.
+pub fn page_kind(document: &Html) -> Result<Option<PageKind>, DownApkError<'static>> {
+    let final_download_link_selector = selector("a[rel='nofollow'][data-google-vignette='false']")?;
+    let download_button_selector = selector("a.accent_bg.btn.btn-flat.downloadButton")?;
+    let table_row_selector = selector("div[class='table-row headerFont']")?;
+
+    let kind = if document
+        .select(&final_download_link_selector)
+        .next()
+        .is_some()
+    {
+        Some(PageKind::Download)
+    } else if document.select(&download_button_selector).next().is_some() {
+        Some(PageKind::Variant)
+    } else if document.select(&table_row_selector).next().is_some() {
+        Some(PageKind::Release)
+    } else {
+        None
+    };
+    Ok(kind)
+}
+
+/// Returns the URL of the variant page a download page URL belongs to, e.g.
+/// `.../app-1-0-android-apk-download/download/?key=...` gives `.../app-1-0-android-apk-download/`.
+fn variant_url_from_download_url(url: &str) -> Option<String> {
+    url.rfind("/download/")
+        .map(|index| url[..index + 1].to_string())
+}
+
+/// Returns the URL of the app page a release, variant or download page belongs to, e.g.
+/// `https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/` gives
+/// `https://www.apkmirror.com/apk/google-inc/youtube/`.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::app_url;
+///
+/// assert_eq!(
+///     app_url("https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/"),
+///     Some("https://www.apkmirror.com/apk/google-inc/youtube/".to_string())
+/// );
+/// assert_eq!(app_url("https://www.apkmirror.com/uploads/"), None);
+/// ```
+pub fn app_url(url: &str) -> Option<String> {
+    let start = url.find("/apk/")? + "/apk/".len();
+    let mut end = start;
+    for _ in 0..2 {
+        let segment = url[end..].find('/').filter(|len| *len > 0)?;
+        end += segment + 1;
+    }
+    Some(url[..end].to_string())
+}
 
-        let download_link = document.select(&download_button_selector).next();
+/// How many release pages `ApkMirror::search_apps` and `ApkMirror::developer_apps`
+/// read at the same time to find the package IDs of the apps.
+pub const PACKAGE_LOOKUPS: usize = 4;
 
-        let final_download_link = match download_link {
-            Some(download_link) => {
-                pb.set_message("Found download link page, trying to get final download link");
-                let download_link = self.absolute_url(download_link.value().attr("href").unwrap());
+/// How many pages of a developer's uploads in a row may only list apps that
+/// were already found before `ApkMirror::developer_apps` stops crawling.
+pub const DEVELOPER_PAGES_WITHOUT_NEW_APPS: usize = 3;
 
-                let res = self.client.get(download_link).send().await?.text().await?;
+/// Returns the slug of a developer given as a slug, e.g. `google-inc`, or as
+/// the URL of the developer page or of any page of one of their apps.
+///
+/// # Example
+///
+/// ```
+/// use downapk::apkmirror::developer_slug;
+///
+/// assert_eq!(developer_slug("google-inc"), Some("google-inc".to_string()));
+/// assert_eq!(
+///     developer_slug("https://www.apkmirror.com/apk/google-inc/youtube/"),
+///     Some("google-inc".to_string())
+/// );
+/// assert_eq!(developer_slug("https://www.apkmirror.com/uploads/"), None);
+/// ```
+pub fn developer_slug(developer: &str) -> Option<String> {
+    let developer = developer.trim();
+    let slug = match developer.find("/apk/") {
+        Some(index) => developer[index + "/apk/".len()..].split('/').next()?,
+        None => developer,
+    };
+    let valid = !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| slug.to_string())
+}
 
-                let document = Html::parse_document(&res);
+/// Returns the package ID shown in the "appspec" rows of a page, e.g. the
+/// `Package: com.google.android.youtube` line of a variant page.
+fn parse_package(document: &Html) -> Result<Option<String>, DownApkError<'static>> {
+    let package = parse_appspecs(document)?
+        .into_iter()
+        .flat_map(|(_, lines)| lines)
+        .find_map(|line| {
+            line.strip_prefix("Package:")
+                .map(|package| package.trim().to_string())
+        });
+    Ok(package)
+}
 
-                let final_download_link = document.select(&final_download_link_selector).next();
+/// Parses the "appspec" rows of a variant page, returning the name of the icon
+/// of each row (e.g. `apkm-icon-file`) with the lines of text it holds.
+fn parse_appspecs(document: &Html) -> Result<Vec<(String, Vec<String>)>, DownApkError<'static>> {
+    let row_selector = selector("div.appspec-row")?;
+    let icon_selector = selector("use")?;
+    let value_selector = selector("div.appspec-value")?;
+
+    let mut specs = vec![];
+    for row in document.select(&row_selector) {
+        let icon = row
+            .select(&icon_selector)
+            .next()
+            // `xlink:href` is namespaced, so it is matched by its local name
+            .and_then(|icon| icon.value().attrs().find(|(name, _)| *name == "href"))
+            .map(|(_, href)| href)
+            .unwrap_or_default()
+            .trim_start_matches('#')
+            .to_string();
+        let lines = match row.select(&value_selector).next() {
+            Some(value) => value
+                .text()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            None => continue,
+        };
+        specs.push((icon, lines));
+    }
+    Ok(specs)
+}
 
-                match final_download_link {
-                    Some(final_download_link) => {
-                        let final_download_link = self.absolute_url(
-                            final_download_link
-                                .value()
-                                .attr("href")
-                                .expect("Could not get final download link"),
-                        );
-                        pb.set_message(format!(
-                            "Found final download link: {}",
-                            final_download_link
-                        ));
-                        final_download_link.to_string()
+/// Parses the details of the single file offered by a variant page.
+///
+/// # Returns
+///
+/// A `Result` containing the `DownloadApkMirror`, whose `download_link` is still the
+/// variant page URL, or `None` if the file does not match the optional parameters.
+fn parse_variant_page(
+    url: &str,
+    document: &Html,
+    apk_type: Option<ApkType>,
+    arch_: Option<&str>,
+    dpi: Option<&str>,
+) -> Result<Option<DownloadApkMirror>, DownApkError<'static>> {
+    let badge_selector = selector("span.apkm-badge")?;
+    let download_button_selector = selector("a.accent_bg.btn.btn-flat.downloadButton")?;
+
+    let mut item = DownloadApkMirror {
+        version: String::new(),
+        download_link: url.to_string(),
+        variant_url: url.to_string(),
+        apk_type: ApkType::Apk,
+        arch: String::new(),
+        min_version: String::new(),
+        screen_dpi: String::new(),
+    };
+
+    let is_bundle = |text: String| text.to_uppercase().contains("BUNDLE");
+    if let Some(badge) = document.select(&badge_selector).next() {
+        if is_bundle(badge.text().collect()) {
+            item.apk_type = ApkType::Bundle;
+        }
+    } else if let Some(button) = document.select(&download_button_selector).next() {
+        if is_bundle(button.text().collect()) {
+            item.apk_type = ApkType::Bundle;
+        }
+    }
+
+    for (icon, lines) in parse_appspecs(document)? {
+        match icon.as_str() {
+            "apkm-icon-file" => {
+                for line in lines {
+                    if let Some(version) = line.strip_prefix("Version:") {
+                        item.version = version
+                            .split(" (")
+                            .next()
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string();
+                    } else if !line.contains(':') && !line.ends_with("downloads") {
+                        item.arch = line;
                     }
-                    None => Err(DownApkError::from("No final download link found"))?,
                 }
             }
-            None => Err(DownApkError::from("No download link found"))?,
-        };
-        pb.set_message("Finished getting download link");
-        Ok(final_download_link)
+            "apkm-icon-sdk" => {
+                if let Some(min) = lines.first().and_then(|line| line.strip_prefix("Min:")) {
+                    let min = min.split(" (").next().unwrap_or_default().trim();
+                    item.min_version = format!("{}+", min);
+                }
+            }
+            "apkm-icon-dpi" => {
+                item.screen_dpi = lines.into_iter().next().unwrap_or_default();
+            }
+            _ => continue,
+        }
     }
 
-    // ... other methods here ...
+    let matches = apk_type.is_none_or(|apk_type| apk_type == item.apk_type)
+        && arch_.is_none_or(|arch_| arch_ == item.arch)
+        && dpi.is_none_or(|dpi| dpi == item.screen_dpi);
+    Ok(matches.then_some(item))
+}
+
+/// Reads the cookies saved to `path`, skipping expired ones. Returns an empty
+/// store if there is no file or it cannot be read.
+fn load_cookies(path: Option<&Path>) -> cookie_store::CookieStore {
+    path.and_then(|path| std::fs::File::open(path).ok())
+        .and_then(|file| cookie_store::serde::json::load(BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+/// Fetches the HTML of the specified URL, checking that an anti-bot challenge
+/// or interstitial was not served in place of the requested page.
+///
+/// # Returns
+///
+/// A `Result` containing the HTML body, a `DownApkError::Challenge` carrying the
+/// offending page, or a `DownApkError::Reqwest` if the server responded with
+/// another error status, such as a 404 or 502.
+///
+/// Requests failing with a network error are retried up to `retries` times,
+/// waiting a second longer before each attempt.
+async fn fetch_html(
+    client: &Client,
+    url: &str,
+    retries: u32,
+) -> Result<String, DownApkError<'static>> {
+    let mut attempt = 0;
+    let res = loop {
+        match client.get(url).send().await {
+            Ok(res) => break res,
+            Err(e) if attempt < retries && (e.is_connect() || e.is_timeout() || e.is_request()) => {
+                attempt += 1;
+                tokio::time::sleep(Duration::from_secs(attempt.into())).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+    let status = res.status();
+    let status_error = res.error_for_status_ref().err();
+    let html = res.text().await?;
+
+    match (detect_challenge(status, &html), status_error) {
+        (Some(reason), _) => Err(DownApkError::Challenge(ChallengePage {
+            url: url.to_string(),
+            status: status.as_u16(),
+            reason: reason.to_string(),
+            html,
+        })),
+        // Error pages must not be cached or parsed as empty results
+        (None, Some(e)) => Err(e.into()),
+        (None, None) => Ok(html),
+    }
+}
+
+/// Returns whether fetching a page failed because the session was rejected,
+/// either with a challenge page or a 401 response.
+fn rejected_session(error: &DownApkError<'static>) -> bool {
+    match error {
+        DownApkError::Challenge(_) => true,
+        DownApkError::Reqwest(e) => e.status() == Some(StatusCode::UNAUTHORIZED),
+        _ => false,
+    }
 }
 
 /// Downloads multiple APK files from APKMirror based on the provided vector of DownloadApkMirror structs.
@@ -661,9 +1783,13 @@ impl ApkMirror {
 /// * `package_name` - The package name of the APK file.
 /// * `output_dir` - The output directory to download the APK files to.
 ///
+/// Like `single_file_download`, it only goes through a proxy set in the
+/// environment, use `ApkMirror::download_files` to download through the proxy
+/// of an instance.
+///
 /// # Returns
 ///
-/// A `Result` containing `()` or an `Error` if the download fails.
+/// A `Result` containing a `DownloadedFile` for each file or an `Error` if the download fails.
 ///
 /// # Example
 ///
@@ -678,22 +1804,110 @@ impl ApkMirror {
 /// }
 /// ```
 pub async fn multiple_file_download(
-    downlinks: &Vec<DownloadApkMirror>,
+    downlinks: &[DownloadApkMirror],
     package_name: &str,
     output_dir: &str,
-) -> Result<(), DownApkError<'static>> {
-    for item in downlinks {
-        single_file_download(item, package_name, output_dir).await?;
-    }
+) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+    concurrent_file_download(downlinks, package_name, output_dir, 1).await
+}
 
-    Ok(())
+/// Downloads multiple APK files like `multiple_file_download`, with up to
+/// `concurrency` downloads running at the same time.
+///
+/// The files are downloaded with a default client, which only goes through a
+/// proxy set in the `HTTP_PROXY`, `HTTPS_PROXY` or `ALL_PROXY` environment
+/// variables. Use `ApkMirror::download_files` to download through the proxy
+/// of an instance, or `concurrent_file_download_with_client`.
+///
+/// # Returns
+///
+/// A `Result` containing a `DownloadedFile` for each file, in the order of
+/// `downlinks`, or the first `Error` a download fails with.
+///
+/// # Example
+///
+/// ```no_run
+/// use downapk::apkmirror::{ApkMirror, concurrent_file_download};
+///
+/// #[tokio::main]
+/// async fn main() {
+///    let apk_mirror = ApkMirror::new().await.unwrap();
+///    let downloads = apk_mirror.download_from_url("https://www.apkmirror.com/apk/instagram/instagram-lite/instagram-lite-390-0-0-9-116-release/", None, None, None).await.unwrap();
+///    concurrent_file_download(&downloads, "com.instagram.lite", "downloads", 4).await.unwrap();
+/// }
+/// ```
+pub async fn concurrent_file_download(
+    downlinks: &[DownloadApkMirror],
+    package_name: &str,
+    output_dir: &str,
+    concurrency: usize,
+) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+    concurrent_file_download_with_client(
+        &Client::new(),
+        downlinks,
+        package_name,
+        output_dir,
+        concurrency,
+    )
+    .await
+}
+
+/// Downloads multiple APK files like `concurrent_file_download`, sending the
+/// requests with `client`, e.g. to go through a proxy.
+///
+/// # Example
+///
+/// ```no_run
+/// use downapk::apkmirror::{concurrent_file_download_with_client, ApkMirror};
+/// use reqwest::{Client, Proxy};
+///
+/// #[tokio::main]
+/// async fn main() {
+///    let apk_mirror = ApkMirror::new().await.unwrap();
+///    let downloads = apk_mirror.download_from_url("https://www.apkmirror.com/apk/instagram/instagram-lite/instagram-lite-390-0-0-9-116-release/", None, None, None).await.unwrap();
+///    let client = Client::builder().proxy(Proxy::all("socks5h://127.0.0.1:1080").unwrap()).build().unwrap();
+///    concurrent_file_download_with_client(&client, &downloads, "com.instagram.lite", "downloads", 4).await.unwrap();
+/// }
+/// ```
+pub async fn concurrent_file_download_with_client(
+    client: &Client,
+    downlinks: &[DownloadApkMirror],
+    package_name: &str,
+    output_dir: &str,
+    concurrency: usize,
+) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+    download_files(
+        client,
+        default_reporter()?.as_ref(),
+        downlinks,
+        package_name,
+        output_dir,
+        concurrency,
+    )
+    .await
+}
+
+/// Downloads `downlinks` with `client`, reporting to `reporter`, see `concurrent_file_download`.
+async fn download_files(
+    client: &Client,
+    reporter: &dyn ProgressReporter,
+    downlinks: &[DownloadApkMirror],
+    package_name: &str,
+    output_dir: &str,
+    concurrency: usize,
+) -> Result<Vec<DownloadedFile>, DownApkError<'static>> {
+    stream::iter(downlinks)
+        .map(|item| download_file(client, reporter, item, package_name, output_dir))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await
 }
 
 /// Downloads APK files from APKMirror based on the provided DownloadApkMirror.
 /// Creates the output directory if it doesn't exist.
 /// Downloads each file to the output directory, using the package name, version, arch, dpi
 /// and extension to construct a filename.
-/// Shows a progress bar while downloading.
+/// Shows a progress bar while downloading and hashes the file as it is written.
 ///
 /// # Arguments
 ///
@@ -701,9 +1915,14 @@ pub async fn multiple_file_download(
 /// * `package_name` - The package name of the APK file.
 /// * `output_dir` - The output directory to download the APK files to.
 ///
+/// The file is downloaded with a default client, which only goes through a
+/// proxy set in the `HTTP_PROXY`, `HTTPS_PROXY` or `ALL_PROXY` environment
+/// variables. Use `ApkMirror::download_file` to download through the proxy of
+/// an instance, or `single_file_download_with_client`.
+///
 /// # Returns
 ///
-/// A `Result` containing `()` or an `Error` if the download fails.
+/// A `Result` containing the `DownloadedFile` with its path, size and SHA-256 hash or an `Error` if the download fails.
 ///
 /// # Example
 ///
@@ -721,16 +1940,53 @@ pub async fn single_file_download(
     item: &DownloadApkMirror,
     package_name: &str,
     output_dir: &str,
-) -> Result<(), DownApkError<'static>> {
-    // if output_dir is not present, create it
-    match tokio::fs::create_dir(output_dir).await {
-        Ok(_) => {}
-        Err(e) => {
-            if e.kind() != std::io::ErrorKind::AlreadyExists {
-                return Err(DownApkError::from(e));
-            }
-        }
-    };
+) -> Result<DownloadedFile, DownApkError<'static>> {
+    single_file_download_with_client(&Client::new(), item, package_name, output_dir).await
+}
+
+/// Downloads an APK file like `single_file_download`, sending the requests
+/// with `client`, e.g. to go through a proxy.
+///
+/// # Example
+///
+/// ```no_run
+/// use downapk::apkmirror::{single_file_download_with_client, ApkMirror};
+/// use reqwest::{Client, Proxy};
+///
+/// #[tokio::main]
+/// async fn main() {
+///    let apk_mirror = ApkMirror::new().await.unwrap();
+///    let downloads = apk_mirror.download_from_url("https://www.apkmirror.com/apk/instagram/instagram-lite/instagram-lite-390-0-0-9-116-release/", None, Some("arm64-v8a"), None).await.unwrap();
+///    let client = Client::builder().proxy(Proxy::all("http://proxy.example:8080").unwrap()).build().unwrap();
+///    single_file_download_with_client(&client, &downloads[0], "com.instagram.lite", "downloads").await.unwrap();
+/// }
+/// ```
+pub async fn single_file_download_with_client(
+    client: &Client,
+    item: &DownloadApkMirror,
+    package_name: &str,
+    output_dir: &str,
+) -> Result<DownloadedFile, DownApkError<'static>> {
+    download_file(
+        client,
+        default_reporter()?.as_ref(),
+        item,
+        package_name,
+        output_dir,
+    )
+    .await
+}
+
+/// Downloads `item` with `client`, reporting to `reporter`, see `single_file_download`.
+async fn download_file(
+    client: &Client,
+    reporter: &dyn ProgressReporter,
+    item: &DownloadApkMirror,
+    package_name: &str,
+    output_dir: &str,
+) -> Result<DownloadedFile, DownApkError<'static>> {
+    // if output_dir is not present, create it along with its parents
+    tokio::fs::create_dir_all(output_dir).await?;
 
     let url = &item.download_link;
     let version = &item.version;
@@ -741,41 +1997,39 @@ pub async fn single_file_download(
         ApkType::Bundle => "apkm",
     };
 
-    let mut res = reqwest::get(url).await?;
+    let mut res = client.get(url).send().await?.error_for_status()?;
     let total_size = res.content_length().unwrap_or_default();
 
-    let pb = match total_size {
-        0 => ProgressBar::new(100),
-        _ => ProgressBar::new(total_size),
-    };
-    pb.set_prefix(format!(" {} Downloading", DOWNLOAD_EMOJI));
-    pb.set_style(ProgressStyle::default_bar().template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})").unwrap());
-
     let output_file = format!(
         "{}_{}_{}_{}.{}",
         package_name, version, arch, dpi, extension
     );
-    let output_path = format!("{}/{}", output_dir, output_file);
-    pb.set_message(format!("File {}", output_file));
-    let mut file = File::create(output_path)
-        .await
-        .expect("Failed to create file");
+    let output_path = PathBuf::from(output_dir).join(&output_file);
+    let pb = reporter.transfer(&output_file, total_size);
+    let mut file = File::create(&output_path).await?;
 
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
     let mut downloaded: u64 = 0;
 
-    while let Some(chunk) = res.chunk().await.expect("Error while downloading file") {
-        file.write_all(&chunk)
-            .await
-            .expect("Error while writing to file");
+    while let Some(chunk) = res.chunk().await? {
+        file.write_all(&chunk).await?;
+        hasher.update(&chunk);
+        size += chunk.len() as u64;
 
         let new = min(downloaded + (chunk.len() as u64), total_size);
         downloaded = new;
         pb.set_position(new);
     }
+    file.flush().await?;
 
-    pb.finish_with_message(format!("Finished downloading file {}", output_file));
+    pb.finish(&format!("Finished downloading file {}", output_file));
 
-    Ok(())
+    Ok(DownloadedFile {
+        path: output_path,
+        size,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,272 @@
+use crate::errors::DownApkError;
+#[cfg(feature = "progress")]
+use console::Emoji;
+#[cfg(feature = "progress")]
+use core::time::Duration;
+#[cfg(feature = "progress")]
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[cfg(feature = "progress")]
+static LOOKING_GLASS: Emoji<'_, '_> = Emoji("🔍  ", "");
+#[cfg(feature = "progress")]
+static SPARKLE: Emoji<'_, '_> = Emoji("✨ ", ":-)");
+#[cfg(feature = "progress")]
+static DOWNLOAD_EMOJI: Emoji<'_, '_> = Emoji("📥 ", ":-)");
+#[cfg(feature = "progress")]
+static TRUCK: Emoji<'_, '_> = Emoji("🚚  ", "");
+
+/// The stage of work a progress task belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Getting the cookies of a new session.
+    Initialise,
+    /// Searching releases.
+    Search,
+    /// Getting the download links of variants.
+    DownloadLinks,
+    /// Downloading a file.
+    Download,
+}
+
+impl Stage {
+    /// Returns a short human readable name of the stage.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Initialise => "Initialise",
+            Stage::Search => "Search",
+            Stage::DownloadLinks => "Get file download links",
+            Stage::Download => "Downloading",
+        }
+    }
+}
+
+/// Reports the progress of the work done by an `ApkMirror` instance.
+///
+/// Each unit of work is reported through the `ProgressTask` returned when it starts.
+pub trait ProgressReporter: Send + Sync {
+    /// Starts a task of unknown length, such as fetching and parsing pages.
+    fn task(&self, stage: Stage, message: &str) -> Box<dyn ProgressTask>;
+
+    /// Starts the transfer of `total` bytes, `0` if the size is unknown.
+    fn transfer(&self, name: &str, total: u64) -> Box<dyn ProgressTask>;
+}
+
+/// A unit of work started by a `ProgressReporter`.
+pub trait ProgressTask: Send + Sync {
+    /// Describes what the task is currently doing.
+    fn set_message(&self, message: &str);
+
+    /// Reports a notable event, such as a skipped item, without changing the message.
+    fn println(&self, message: &str);
+
+    /// Reports how many bytes of a transfer were done.
+    fn set_position(&self, position: u64);
+
+    /// Ends the task successfully.
+    fn finish(&self, message: &str);
+
+    /// Ends the task unsuccessfully.
+    fn abandon(&self, message: &str);
+}
+
+/// Returns the reporter used unless another one is set: an `IndicatifReporter`,
+/// or a `SilentReporter` without the `progress` feature.
+pub(crate) fn default_reporter() -> Result<Arc<dyn ProgressReporter>, DownApkError<'static>> {
+    #[cfg(feature = "progress")]
+    return Ok(Arc::new(IndicatifReporter::new()?));
+    #[cfg(not(feature = "progress"))]
+    return Ok(Arc::new(SilentReporter));
+}
+
+/// Shows spinners and progress bars on the terminal with indicatif.
+///
+/// Tasks running at the same time are shown one below the other.
+#[cfg(feature = "progress")]
+pub struct IndicatifReporter {
+    multi: MultiProgress,
+    spinner: ProgressStyle,
+    bar: ProgressStyle,
+}
+
+#[cfg(feature = "progress")]
+impl IndicatifReporter {
+    /// Creates a reporter drawing on stderr.
+    pub fn new() -> Result<Self, DownApkError<'static>> {
+        Ok(IndicatifReporter {
+            multi: MultiProgress::new(),
+            spinner: ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {wide_msg}")?
+                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
+            bar: ProgressStyle::default_bar().template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?,
+        })
+    }
+}
+
+#[cfg(feature = "progress")]
+impl ProgressReporter for IndicatifReporter {
+    fn task(&self, stage: Stage, message: &str) -> Box<dyn ProgressTask> {
+        let emoji = match stage {
+            Stage::Initialise => SPARKLE,
+            Stage::Search => LOOKING_GLASS,
+            Stage::DownloadLinks => TRUCK,
+            Stage::Download => DOWNLOAD_EMOJI,
+        };
+        let pb = self.multi.add(ProgressBar::new(40));
+        pb.set_style(self.spinner.clone());
+        pb.set_prefix(format!(" {} {}", emoji, stage.name()));
+        pb.set_message(message.to_string());
+        pb.enable_steady_tick(Duration::from_millis(100));
+        Box::new(IndicatifTask(pb))
+    }
+
+    fn transfer(&self, name: &str, total: u64) -> Box<dyn ProgressTask> {
+        let pb = match total {
+            0 => ProgressBar::new(100),
+            _ => ProgressBar::new(total),
+        };
+        let pb = self.multi.add(pb);
+        pb.set_prefix(format!(" {} {}", DOWNLOAD_EMOJI, Stage::Download.name()));
+        pb.set_style(self.bar.clone());
+        pb.set_message(format!("File {}", name));
+        Box::new(IndicatifTask(pb))
+    }
+}
+
+#[cfg(feature = "progress")]
+struct IndicatifTask(ProgressBar);
+
+#[cfg(feature = "progress")]
+impl ProgressTask for IndicatifTask {
+    fn set_message(&self, message: &str) {
+        self.0.set_message(message.to_string());
+    }
+
+    fn println(&self, message: &str) {
+        self.0.println(message);
+    }
+
+    fn set_position(&self, position: u64) {
+        self.0.set_position(position);
+    }
+
+    fn finish(&self, message: &str) {
+        self.0.finish_with_message(message.to_string());
+    }
+
+    fn abandon(&self, message: &str) {
+        self.0.abandon_with_message(message.to_string());
+    }
+}
+
+/// Reports nothing.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SilentReporter;
+
+impl ProgressReporter for SilentReporter {
+    fn task(&self, _stage: Stage, _message: &str) -> Box<dyn ProgressTask> {
+        Box::new(SilentReporter)
+    }
+
+    fn transfer(&self, _name: &str, _total: u64) -> Box<dyn ProgressTask> {
+        Box::new(SilentReporter)
+    }
+}
+
+impl ProgressTask for SilentReporter {
+    fn set_message(&self, _message: &str) {}
+
+    fn println(&self, _message: &str) {}
+
+    fn set_position(&self, _position: u64) {}
+
+    fn finish(&self, _message: &str) {}
+
+    fn abandon(&self, _message: &str) {}
+}
+
+/// Writes one plain line per message to stderr, suitable for log files and CI output.
+///
+/// Transfers are logged when they start, at every 10% and when they end.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogReporter;
+
+impl ProgressReporter for LogReporter {
+    fn task(&self, stage: Stage, message: &str) -> Box<dyn ProgressTask> {
+        let task = LogTask::new(stage, 0);
+        task.log(message);
+        Box::new(task)
+    }
+
+    fn transfer(&self, name: &str, total: u64) -> Box<dyn ProgressTask> {
+        let task = LogTask::new(Stage::Download, total);
+        match total {
+            0 => task.log(&format!("File {}", name)),
+            _ => task.log(&format!("File {} ({} bytes)", name, total)),
+        }
+        Box::new(task)
+    }
+}
+
+/// A task of a `LogReporter`.
+pub(crate) struct LogTask {
+    stage: Stage,
+    /// The size of a transfer, `0` if unknown or not a transfer.
+    total: u64,
+    /// The last percentage of the transfer that was logged.
+    logged_percent: AtomicU64,
+}
+
+impl LogTask {
+    /// Creates a task of `stage`, transferring `total` bytes if it is not `0`.
+    pub(crate) fn new(stage: Stage, total: u64) -> Self {
+        LogTask {
+            stage,
+            total,
+            logged_percent: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the 10% step reached at `position` if it was not logged yet,
+    /// remembering it as logged.
+    pub(crate) fn step(&self, position: u64) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        let percent = position.saturating_mul(100) / self.total / 10 * 10;
+        (percent > self.logged_percent.fetch_max(percent, Ordering::Relaxed)).then_some(percent)
+    }
+
+    fn log(&self, message: &str) {
+        // Failing to write progress is not worth failing the work for
+        let _ = writeln!(std::io::stderr(), "[{}] {}", self.stage.name(), message);
+    }
+}
+
+impl ProgressTask for LogTask {
+    fn set_message(&self, message: &str) {
+        self.log(message);
+    }
+
+    fn println(&self, message: &str) {
+        self.log(message);
+    }
+
+    fn set_position(&self, position: u64) {
+        if let Some(percent) = self.step(position) {
+            self.log(&format!(
+                "{}% ({} of {} bytes)",
+                percent, position, self.total
+            ));
+        }
+    }
+
+    fn finish(&self, message: &str) {
+        self.log(message);
+    }
+
+    fn abandon(&self, message: &str) {
+        self.log(message);
+    }
+}
@@ -95,3 +95,1084 @@ async fn test_download() {
         Err(e) => panic!("Error while downloading file. Err: {}", e),
     }
 }
+
+#[test]
+fn test_detect_challenge() {
+    use reqwest::StatusCode;
+
+    let cloudflare = "<html><head><title>Just a moment...</title></head><body><div id=\"cf-browser-verification\"></div></body></html>";
+    assert_eq!(
+        challenge::detect_challenge(StatusCode::FORBIDDEN, cloudflare),
+        Some("Cloudflare browser check")
+    );
+    assert_eq!(
+        challenge::detect_challenge(StatusCode::TOO_MANY_REQUESTS, "<html></html>"),
+        Some("Rate limited by the server")
+    );
+    assert_eq!(
+        challenge::detect_challenge(
+            StatusCode::OK,
+            "<html><body><button class=\"searchButton\"></button></body></html>"
+        ),
+        None
+    );
+}
+
+#[test]
+fn test_save_challenge() {
+    let dir = std::env::temp_dir().join(format!("downapk-challenge-test-{}", std::process::id()));
+    let page = ChallengePage {
+        url: "https://www.apkmirror.com/".to_string(),
+        status: 403,
+        reason: "Cloudflare browser check".to_string(),
+        html: "<html><title>Just a moment...</title></html>".to_string(),
+    };
+
+    let path = page.save(dir.join("challenges")).unwrap();
+    let name = path.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("challenge-403-"), "{}", name);
+    assert!(name.ends_with(".html"), "{}", name);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), page.html);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_bootstrap_unexpected_page() {
+    let build = |base_url: String| async move {
+        ApkMirror::builder()
+            .base_url(&base_url)
+            .progress(progress::SilentReporter)
+            .build()
+            .await
+    };
+
+    let base_url = serve_pages(vec![("/", "<p>Down for maintenance</p>".to_string())]).await;
+    match build(base_url).await {
+        Err(DownApkError::Other(e)) => assert!(e.contains("search form"), "{}", e),
+        result => panic!("unexpected result {:?}", result.map(|_| ())),
+    }
+
+    let base_url = serve_pages(vec![("/", "<title>Just a moment...</title>".to_string())]).await;
+    match build(base_url).await {
+        Err(DownApkError::Challenge(page)) => assert_eq!(page.reason, "Cloudflare browser check"),
+        result => panic!("unexpected result {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_serialize_download_apkmirror() {
+    let item = DownloadApkMirror {
+        version: "390.0.0.9.116".to_string(),
+        download_link: "https://www.apkmirror.com/wp-content/themes/APKMirror/download.php?id=1"
+            .to_string(),
+        variant_url: "https://www.apkmirror.com/apk/instagram/instagram-lite/instagram-lite-390-0-0-9-116-release/instagram-lite-390-0-0-9-116-android-apk-download/".to_string(),
+        apk_type: ApkType::Bundle,
+        arch: "arm64-v8a".to_string(),
+        min_version: "Android 5.0+".to_string(),
+        screen_dpi: "nodpi".to_string(),
+    };
+    let json = serde_json::to_value(&item).unwrap();
+    assert_eq!(json["apk_type"], "BUNDLE");
+    assert_eq!(json["arch"], "arm64-v8a");
+    assert_eq!(json["version"], "390.0.0.9.116");
+}
+
+#[test]
+fn test_metadata() {
+    use metadata::{parse_downloads, parse_file_size, parse_uploaded};
+
+    assert_eq!(parse_file_size("830 KB"), Some(849_920));
+    assert_eq!(parse_file_size("1.5 GB"), Some(1_610_612_736));
+    assert_eq!(parse_file_size("123 bytes"), Some(123));
+    assert_eq!(parse_file_size("unknown"), None);
+    assert_eq!(parse_downloads("45"), Some(45));
+    assert_eq!(parse_downloads("3.4M"), Some(3_400_000));
+    assert_eq!(parse_downloads(""), None);
+    assert_eq!(
+        parse_uploaded("December 25, 2023 at 12:05AM UTC").map(|t| t.unix_timestamp()),
+        Some(1_703_462_700)
+    );
+    assert_eq!(
+        parse_uploaded("December 25, 2023").map(|t| t.unix_timestamp()),
+        Some(1_703_462_400)
+    );
+    assert_eq!(parse_uploaded("yesterday"), None);
+
+    let link = ExtractedLink {
+        uploaded_at: parse_uploaded("January 5, 2024 at 3:12PM UTC"),
+        ..Default::default()
+    };
+    let json = serde_json::to_value(&link).unwrap();
+    assert_eq!(json["uploaded_at"], "2024-01-05T15:12:00Z");
+    assert_eq!(json["file_size_bytes"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_page_kind() {
+    let release = Html::parse_document(
+        r#"<div class="table-row headerFont"><div class="table-cell">Variant</div></div>"#,
+    );
+    let variant = Html::parse_document(
+        r#"<a class="accent_bg btn btn-flat downloadButton" href="/apk/a/b/c/d/download/?key=1">Download APK</a>"#,
+    );
+    let download = Html::parse_document(
+        r#"<a rel="nofollow" data-google-vignette="false" href="/wp-content/themes/APKMirror/download.php?id=1">here</a>"#,
+    );
+    let other = Html::parse_document("<p>Nothing to see</p>");
+
+    assert_eq!(page_kind(&release).unwrap(), Some(PageKind::Release));
+    assert_eq!(page_kind(&variant).unwrap(), Some(PageKind::Variant));
+    assert_eq!(page_kind(&download).unwrap(), Some(PageKind::Download));
+    assert_eq!(page_kind(&other).unwrap(), None);
+
+    assert_eq!(
+        variant_url_from_download_url(
+            "https://www.apkmirror.com/apk/a/b/b-1-0-release/b-1-0-android-apk-download/download/?key=abc"
+        ),
+        Some("https://www.apkmirror.com/apk/a/b/b-1-0-release/b-1-0-android-apk-download/".to_string())
+    );
+}
+
+#[test]
+fn test_parse_variant_page() {
+    let url = "https://www.apkmirror.com/apk/a/b/b-1-0-release/b-1-0-android-apk-download/";
+    let document = Html::parse_document(
+        r##"<div class="appspec-row">
+            <svg class="icon"><use xlink:href="#apkm-icon-file"></use></svg>
+            <div class="appspec-value">Version: 1.0.3 (103)<br>arm64-v8a<br>Package: com.example.b<br>1,024 downloads</div>
+        </div>
+        <div class="appspec-row">
+            <svg class="icon"><use xlink:href="#apkm-icon-sdk"></use></svg>
+            <div class="appspec-value">Min: Android 8.0 (Oreo, API 26)<br>Target: Android 14 (API 34)</div>
+        </div>
+        <div class="appspec-row">
+            <svg class="icon"><use xlink:href="#apkm-icon-dpi"></use></svg>
+            <div class="appspec-value">nodpi</div>
+        </div>
+        <a class="accent_bg btn btn-flat downloadButton" href="/download/">Download APK Bundle</a>"##,
+    );
+
+    let item = parse_variant_page(url, &document, None, None, None)
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.version, "1.0.3");
+    assert_eq!(item.arch, "arm64-v8a");
+    assert_eq!(item.min_version, "Android 8.0+");
+    assert_eq!(item.screen_dpi, "nodpi");
+    assert_eq!(item.apk_type, ApkType::Bundle);
+    assert_eq!(item.variant_url, url);
+
+    let filtered = parse_variant_page(url, &document, None, Some("x86"), None).unwrap();
+    assert!(filtered.is_none());
+}
+
+#[test]
+fn test_log_steps() {
+    use progress::{LogTask, Stage};
+
+    let task = LogTask::new(Stage::Download, 1000);
+    assert_eq!(task.step(50), None);
+    assert_eq!(task.step(100), Some(10));
+    // Repeated positions and positions within the same step are logged once
+    assert_eq!(task.step(100), None);
+    assert_eq!(task.step(199), None);
+    // A jump past several steps only logs the last one reached
+    assert_eq!(task.step(730), Some(70));
+    // Going back does not log an earlier step again
+    assert_eq!(task.step(200), None);
+    assert_eq!(task.step(1000), Some(100));
+    assert_eq!(task.step(1000), None);
+
+    // Transfers of unknown size and plain tasks log no steps
+    assert_eq!(LogTask::new(Stage::Download, 0).step(500), None);
+    assert_eq!(Stage::Initialise.name(), "Initialise");
+}
+
+#[tokio::test]
+async fn test_page_cache() {
+    let dir = std::env::temp_dir().join(format!("downapk-cache-test-{}", std::process::id()));
+    let url = "https://www.apkmirror.com/apk/instagram/instagram-lite/";
+
+    let cache = PageCache::new(&dir, Duration::from_secs(60));
+    assert_eq!(cache.get(url).await, None);
+    cache.put(url, "<html></html>").await.unwrap();
+    assert_eq!(cache.get(url).await.as_deref(), Some("<html></html>"));
+    // Only the page is left, without the temporary file it was written to
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].to_string_lossy().ends_with(".html"));
+    assert_eq!(cache.get("https://www.apkmirror.com/").await, None);
+
+    let refresh = cache.clone().refresh(true);
+    assert_eq!(refresh.get(url).await, None);
+
+    let expired = PageCache::new(&dir, Duration::ZERO);
+    assert_eq!(expired.get(url).await, None);
+
+    // Error responses fail instead of being cached as pages without results
+    let base_url = serve_pages(vec![]).await;
+    let apkmirror = ApkMirror::builder()
+        .base_url(&base_url)
+        .bootstrap(false)
+        .cache(cache)
+        .progress(progress::SilentReporter)
+        .build()
+        .await
+        .unwrap();
+    let missing = format!("{}/apk/missing/", base_url);
+    match apkmirror.extract_root_links(&missing, None).await {
+        Err(DownApkError::Reqwest(e)) => {
+            assert_eq!(e.status(), Some(reqwest::StatusCode::NOT_FOUND))
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert_eq!(
+        PageCache::new(&dir, Duration::from_secs(60))
+            .get(&missing)
+            .await,
+        None
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_restore_session() {
+    let dir = std::env::temp_dir().join(format!("downapk-session-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let cookie_file = dir.join("cookies.json");
+
+    let url = reqwest::Url::parse("https://www.apkmirror.com/").unwrap();
+    let mut store = cookie_store::CookieStore::default();
+    store
+        .parse("__cf_bm=session; Max-Age=3600; Path=/", &url)
+        .unwrap();
+    let mut writer = std::fs::File::create(&cookie_file).unwrap();
+    cookie_store::serde::json::save(&store, &mut writer).unwrap();
+
+    // A restored session does not fetch the homepage, so this works offline
+    let apkmirror = ApkMirror::builder()
+        .cookie_file(&cookie_file)
+        .progress(progress::SilentReporter)
+        .build()
+        .await
+        .unwrap();
+    std::fs::remove_file(&cookie_file).unwrap();
+    apkmirror.save_cookies().unwrap();
+
+    let restored = load_cookies(Some(&cookie_file));
+    assert!(restored.contains("www.apkmirror.com", "/", "__cf_bm"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_rejected_session() {
+    let challenge = "<title>Just a moment...</title>".to_string();
+    let results = format!(
+        "<div class='listWidget'>{}</div>",
+        row(
+            "/apk/example/example/example-1-0-release/",
+            "Example",
+            "1.0"
+        )
+    );
+    let (base_url, requests) = serve_logged_pages(vec![
+        ("/apk/example/", challenge.clone()),
+        ("/apk/example/", results),
+        ("/apk/example/", challenge),
+        ("/", "<button class='searchButton'></button>".to_string()),
+    ])
+    .await;
+
+    // A stored session skips fetching the homepage
+    let dir = std::env::temp_dir().join(format!("downapk-rejected-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let cookie_file = dir.join("cookies.json");
+    let url = reqwest::Url::parse(&base_url).unwrap();
+    let mut store = cookie_store::CookieStore::default();
+    store
+        .parse("session=stale; Max-Age=3600; Path=/", &url)
+        .unwrap();
+    let mut writer = std::fs::File::create(&cookie_file).unwrap();
+    cookie_store::serde::json::save(&store, &mut writer).unwrap();
+
+    let apkmirror = ApkMirror::builder()
+        .base_url(&base_url)
+        .cookie_file(&cookie_file)
+        .progress(progress::SilentReporter)
+        .build()
+        .await
+        .unwrap();
+    assert!(requests.lock().unwrap().is_empty());
+
+    // The rejected session is renewed on the homepage and the page retried once
+    let page = format!("{}/apk/example/", base_url);
+    let results = apkmirror.extract_root_links(&page, None).await.unwrap();
+    assert_eq!(results[0].version, "1.0");
+    assert_eq!(
+        *requests.lock().unwrap(),
+        ["/apk/example/", "/", "/apk/example/"]
+    );
+
+    // Later rejections are returned without fetching the homepage again
+    match apkmirror.extract_root_links(&page, None).await {
+        Err(DownApkError::Challenge(page)) => assert_eq!(page.reason, "Cloudflare browser check"),
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert_eq!(
+        *requests.lock().unwrap(),
+        ["/apk/example/", "/", "/apk/example/", "/apk/example/"]
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_builder() {
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // Serves a homepage with the search form once, returning the request it received
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let len = socket.read(&mut request).await.unwrap();
+        let body = "<html><button class='searchButton'></button></html>";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nSet-Cookie: session=1; Path=/\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request[..len]).to_lowercase()
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-test", HeaderValue::from_static("1"));
+    let apkmirror = ApkMirror::builder()
+        .base_url(format!("{}/", base_url))
+        .user_agent("downapk-test")
+        .headers(headers)
+        .connect_timeout(Duration::from_secs(5))
+        .read_timeout(Duration::from_secs(5))
+        .build()
+        .await
+        .unwrap();
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("get / http/1.1"));
+    assert!(request.contains("user-agent: downapk-test"));
+    assert!(request.contains("x-test: 1"));
+    assert_eq!(
+        apkmirror.absolute_url("/apk/"),
+        format!("{}/apk/", base_url)
+    );
+    assert!(apkmirror
+        .cookies
+        .lock()
+        .unwrap()
+        .iter_unexpired()
+        .any(|cookie| cookie.name() == "session"));
+
+    // Without the bootstrap nothing is fetched
+    ApkMirror::builder()
+        .base_url(base_url)
+        .bootstrap(false)
+        .build()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_search_stream() {
+    use futures_util::{StreamExt, TryStreamExt};
+
+    // Two pages of results, the first linking to the second
+    let first_page = format!(
+        "<div class='listWidget'>{}{}</div><a class='nextpostslink' \
+         href='/page/2/?post_type=app_release&amp;searchtype=apk&amp;s=com.example'>2</a>",
+        row(
+            "/apk/example/example/example-1.3-release/",
+            "Example",
+            "1.3"
+        ),
+        row(
+            "/apk/example/example/example-1.2-release/",
+            "Example",
+            "1.2"
+        ),
+    );
+    let second_page = format!(
+        "<div class='listWidget'>{}{}</div>",
+        row(
+            "/apk/example/example/example-1.1-release/",
+            "Example",
+            "1.1"
+        ),
+        row(
+            "/apk/example/example/example-1.0-release/",
+            "Example",
+            "1.0"
+        ),
+    );
+    let (base_url, requests) = serve_logged_pages(vec![
+        ("/page/2/", second_page),
+        ("/?post_type=app_release", first_page),
+    ])
+    .await;
+    let pages = || -> Vec<bool> {
+        let requests = requests.lock().unwrap();
+        requests
+            .iter()
+            .map(|path| path.starts_with("/page/2/"))
+            .collect()
+    };
+
+    let apkmirror = test_client(&base_url).await;
+
+    let versions: Vec<String> = apkmirror
+        .search_stream("com.example")
+        .map_ok(|link| link.version)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(versions, ["1.3", "1.2", "1.1", "1.0"]);
+    assert_eq!(pages(), [false, true]);
+
+    // Stopping early leaves the next page alone
+    let first: Vec<_> = apkmirror
+        .search_stream("com.example")
+        .take(2)
+        .collect()
+        .await;
+    assert_eq!(first.len(), 2);
+    assert_eq!(
+        first[0].as_ref().unwrap().link,
+        format!("{}/apk/example/example/example-1.3-release/", base_url)
+    );
+    assert_eq!(pages(), [false, true, false]);
+
+    // A bounded search leaves the pages past the bound alone
+    let versions: Vec<String> = apkmirror
+        .search_pages("com.example", 1)
+        .map_ok(|link| link.version)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(versions, ["1.3", "1.2"]);
+    assert_eq!(pages(), [false, true, false, false]);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking() {
+    // The server runs on its own runtime, as the blocking client must not be
+    // used from within one
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let base_url = runtime.block_on(serve_pages(vec![
+        (
+            "/?post_type=app_release",
+            format!(
+                "<div class='listWidget'>{}</div>",
+                row("/apk/example/", "Example", "1.0")
+            ),
+        ),
+        ("/", "<button class='searchButton'></button>".to_string()),
+    ]));
+
+    let apkmirror = blocking::ApkMirror::from_builder(
+        ApkMirror::builder()
+            .base_url(&base_url)
+            .progress(progress::SilentReporter),
+    )
+    .unwrap();
+    let results: Vec<_> = apkmirror
+        .search_iter("com.example")
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].version, "1.0");
+    assert_eq!(results[0].link, format!("{}/apk/example/", base_url));
+}
+
+#[tokio::test]
+async fn test_search_package() {
+    let search = format!(
+        "<div class='listWidget'>{}{}{}</div>",
+        row(
+            "/apk/google-inc/youtube-music/youtube-music-7-0-release/",
+            "YouTube Music",
+            "7.0"
+        ),
+        row(
+            "/apk/google-inc/youtube/youtube-19-0-release/",
+            "YouTube",
+            "19.0"
+        ),
+        row(
+            "/apk/google-inc/youtube/youtube-18-0-release/",
+            "YouTube",
+            "18.0"
+        ),
+    );
+    // The release page of YouTube only shows the package on its variant page
+    let base_url = serve_pages(vec![
+        ("/?post_type=app_release", search),
+        (
+            "/apk/google-inc/youtube-music/youtube-music-7-0-release/",
+            package_page("com.google.android.apps.youtube.music"),
+        ),
+        (
+            "/apk/google-inc/youtube/youtube-19-0-release/youtube-19-0-android-apk-download/",
+            package_page("com.google.android.youtube"),
+        ),
+        (
+            "/apk/google-inc/youtube/youtube-19-0-release/",
+            "<div class='table-row headerFont'><a class='accent_color' \
+             href='/apk/google-inc/youtube/youtube-19-0-release/youtube-19-0-android-apk-download/'>19.0</a></div>"
+                .to_string(),
+        ),
+    ])
+    .await;
+
+    let apkmirror = test_client(&base_url).await;
+    assert_eq!(
+        apkmirror
+            .resolve_package("com.google.android.youtube")
+            .await
+            .unwrap(),
+        Some(format!("{}/apk/google-inc/youtube/", base_url))
+    );
+    let versions: Vec<_> = apkmirror
+        .search_package("com.google.android.youtube", None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|result| result.version)
+        .collect();
+    assert_eq!(versions, ["19.0", "18.0"]);
+    assert!(apkmirror
+        .search_package("com.example.missing", None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_search_apps() {
+    let search = format!(
+        "<div class='listWidget'>{}{}{}</div>",
+        row(
+            "/apk/acme/backup-for-signal/backup-for-signal-2-0-release/",
+            "Backup for Signal",
+            "2.0"
+        ),
+        row(
+            "/apk/signal/signal-private-messenger/signal-private-messenger-7-1-release/",
+            "Signal Private Messenger",
+            "7.1"
+        ),
+        row(
+            "/apk/signal/signal-private-messenger/signal-private-messenger-7-0-release/",
+            "Signal Private Messenger",
+            "7.0"
+        ),
+    );
+    let base_url = serve_pages(vec![
+        ("/?post_type=app_release", search),
+        (
+            "/apk/acme/backup-for-signal/",
+            package_page("com.acme.signalbackup"),
+        ),
+        (
+            "/apk/signal/signal-private-messenger/",
+            package_page("org.thoughtcrime.securesms"),
+        ),
+    ])
+    .await;
+
+    let apkmirror = test_client(&base_url).await;
+    let apps = apkmirror.search_apps("signal").await.unwrap();
+    assert_eq!(apps.len(), 2);
+    assert_eq!(apps[0].name, "Signal Private Messenger");
+    assert_eq!(apps[0].developer, "signal");
+    assert_eq!(apps[0].slug, "signal-private-messenger");
+    assert_eq!(
+        apps[0].package_id.as_deref(),
+        Some("org.thoughtcrime.securesms")
+    );
+    assert_eq!(apps[0].latest_version, "7.1");
+    assert_eq!(apps[0].releases.len(), 2);
+    assert_eq!(apps[1].package_id.as_deref(), Some("com.acme.signalbackup"));
+    assert!(apps[0].score > apps[1].score);
+
+    // Free text is encoded instead of being cut at `&` or `#`
+    let (base_url, requests) = serve_logged_pages(vec![]).await;
+    let apkmirror = test_client(&base_url).await;
+    assert!(apkmirror.search_apps("AT&T C# Google+").await.is_err());
+    assert_eq!(
+        *requests.lock().unwrap(),
+        ["/?post_type=app_release&searchtype=apk&s=at%26t+c%23+google%2b"]
+    );
+}
+
+#[tokio::test]
+async fn test_developer_apps() {
+    let first_page = format!(
+        "<div class='listWidget'>{}{}</div><a class='nextpostslink' href='/apk/signal/page/2/'>Next</a>",
+        row(
+            "/apk/signal/signal-private-messenger/signal-private-messenger-7-1-release/",
+            "Signal Private Messenger",
+            "7.1"
+        ),
+        row(
+            "/apk/signal/signal-private-messenger/signal-private-messenger-7-0-release/",
+            "Signal Private Messenger",
+            "7.0"
+        ),
+    );
+    let second_page = format!(
+        "<div class='listWidget'>{}</div>",
+        row(
+            "/apk/signal/signal-backup/signal-backup-1-2-release/",
+            "Signal Backup",
+            "1.2"
+        ),
+    );
+    let base_url = serve_pages(vec![
+        ("/apk/signal/page/2/", second_page),
+        (
+            "/apk/signal/signal-private-messenger/",
+            package_page("org.thoughtcrime.securesms"),
+        ),
+        (
+            "/apk/signal/signal-backup/",
+            package_page("org.signal.backup"),
+        ),
+        ("/apk/signal/", first_page),
+    ])
+    .await;
+
+    let apkmirror = test_client(&base_url).await;
+    let apps = apkmirror
+        .developer_apps(
+            &format!(
+                "{}/apk/signal/signal-backup/signal-backup-1-2-release/",
+                base_url
+            ),
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(apps.len(), 2);
+    assert_eq!(apps[0].name, "Signal Private Messenger");
+    assert_eq!(apps[0].score, None);
+    assert_eq!(
+        apps[0].package_id.as_deref(),
+        Some("org.thoughtcrime.securesms")
+    );
+    assert_eq!(apps[0].latest_version, "7.1");
+    assert_eq!(apps[0].releases.len(), 2);
+    assert_eq!(apps[1].name, "Signal Backup");
+    assert_eq!(apps[1].package_id.as_deref(), Some("org.signal.backup"));
+    assert_eq!(apps[1].latest_version, "1.2");
+
+    let apps = apkmirror.developer_apps("signal", 1).await.unwrap();
+    assert_eq!(apps.len(), 1);
+
+    assert!(apkmirror.developer_apps("not a slug", 10).await.is_err());
+}
+
+#[tokio::test]
+async fn test_app_feed() {
+    let rss = "<rss version='2.0'><channel><title>YouTube</title>\
+        <item><title><![CDATA[YouTube 19.03.36 beta by Google LLC]]></title>\
+        <link>https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-03-36-release/</link>\
+        <pubDate>Fri, 19 Jan 2024 15:12:00 +0000</pubDate></item>\
+        <item><title>YouTube 19.02.34 by Google LLC</title>\
+        <link>https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/</link></item>\
+        <item><title>No link</title></item>\
+        </channel></rss>";
+    let base_url = serve_pages(vec![("/apk/google-inc/youtube/feed/", rss.to_string())]).await;
+
+    let apkmirror = test_client(&base_url).await;
+    let entries = apkmirror
+        .app_feed(&format!(
+            "{}/apk/google-inc/youtube/youtube-19-02-34-release/",
+            base_url
+        ))
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].title, "YouTube 19.03.36 beta by Google LLC");
+    assert_eq!(entries[0].version, "19.03.36");
+    assert_eq!(
+        entries[0].published_at.map(|t| t.unix_timestamp()),
+        Some(1_705_677_120)
+    );
+    assert_eq!(
+        entries[1].link,
+        "https://www.apkmirror.com/apk/google-inc/youtube/youtube-19-02-34-release/"
+    );
+    assert_eq!(entries[1].published_at, None);
+
+    let atom = "<?xml version='1.0' encoding='UTF-8'?>\
+        <feed xmlns='http://www.w3.org/2005/Atom'><entry>\
+        <title>Signal Private Messenger 7.1.0 by Signal Foundation</title>\
+        <link rel='replies' href='https://www.apkmirror.com/comments/'/>\
+        <link rel='alternate' href='https://www.apkmirror.com/apk/signal/signal/signal-7-1-0-release/'/>\
+        <updated>2024-01-05T15:12:00Z</updated></entry></feed>";
+    let entries = feed::parse_feed(atom).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].version, "7.1.0");
+    assert_eq!(
+        entries[0].link,
+        "https://www.apkmirror.com/apk/signal/signal/signal-7-1-0-release/"
+    );
+    assert_eq!(
+        entries[0].published_at.map(|t| t.unix_timestamp()),
+        Some(1_704_467_520)
+    );
+    assert!(feed::parse_feed("<rss><channel>").is_err());
+}
+
+#[tokio::test]
+async fn test_release_info() {
+    let release = "<h1>YouTube 19.02.34</h1><h3><a href='/apk/google-inc/'>Google LLC</a></h3>\
+        <a href='/categories/video_players_editors/'>Video Players &amp; Editors</a>\
+        <div id='description'><div class='notes'><p>Watch videos.</p><p>Share them.</p></div></div>\
+        <div id='whatsnew'><div class='notes'>Bug fixes</div></div>\
+        <div class='table-row headerFont'><a class='accent_color' \
+        href='/apk/google-inc/youtube/youtube-19-02-34-release/youtube-19-02-34-android-apk-download/'>19.02.34</a></div>";
+    let variant = "<h1>YouTube 19.02.34 (nodpi)</h1>\
+        <div class='appspec-row'><svg><use xlink:href='#apkm-icon-file'></use></svg>\
+        <div class='appspec-value'>Version: 19.02.34 (1543)<br>Package: com.google.android.youtube</div></div>\
+        <div class='appspec-row'><svg><use xlink:href='#apkm-icon-calendar'></use></svg>\
+        <div class='appspec-value'><span class='datetime_utc'>January 5, 2024 at 3:12PM UTC</span></div></div>\
+        <div id='permissions'><ul><li>android.permission.INTERNET</li><li>android.permission.CAMERA</li></ul></div>\
+        <span class='wordbreak-all'><b>SHA-1:</b> 24:BB:24</span><span class='wordbreak-all'><b>SHA-256:</b> 3D:7A:12</span>";
+    let base_url = serve_pages(vec![
+        (
+            "/apk/google-inc/youtube/youtube-19-02-34-release/youtube-19-02-34-android-apk-download/",
+            variant.to_string(),
+        ),
+        (
+            "/apk/google-inc/youtube/youtube-19-02-34-release/",
+            release.to_string(),
+        ),
+        ("/apk/google-inc/youtube/", release.to_string()),
+    ])
+    .await;
+
+    let apkmirror = test_client(&base_url).await;
+    let info = apkmirror
+        .release_info(&format!(
+            "{}/apk/google-inc/youtube/youtube-19-02-34-release/",
+            base_url
+        ))
+        .await
+        .unwrap();
+    assert_eq!(info.title, "YouTube 19.02.34");
+    assert_eq!(info.version, "19.02.34");
+    assert_eq!(
+        info.package_id.as_deref(),
+        Some("com.google.android.youtube")
+    );
+    assert_eq!(info.developer, "Google LLC");
+    assert_eq!(info.category.as_deref(), Some("Video Players & Editors"));
+    assert_eq!(info.description, "Watch videos.\nShare them.");
+    assert_eq!(info.whats_new, "Bug fixes");
+    assert_eq!(
+        info.permissions,
+        ["android.permission.INTERNET", "android.permission.CAMERA"]
+    );
+    assert_eq!(info.signature.as_deref(), Some("3D:7A:12"));
+    assert_eq!(info.uploaded, "January 5, 2024 at 3:12PM UTC");
+    assert_eq!(
+        info.uploaded_at.map(|t| t.unix_timestamp()),
+        Some(1_704_467_520)
+    );
+
+    let app = apkmirror
+        .app_info(&format!(
+            "{}/apk/google-inc/youtube/youtube-19-02-34-release/",
+            base_url
+        ))
+        .await
+        .unwrap();
+    assert_eq!(app.developer, "Google LLC");
+    assert_eq!(app.url, format!("{}/apk/google-inc/youtube/", base_url));
+}
+
+#[tokio::test]
+async fn test_whats_new() {
+    let release = "<h1>YouTube 19.02.34</h1>\
+        <div id='whatsnew'><div class='notes'><ul><li>Bug fixes</li><li>Faster startup</li></ul></div></div>";
+    let base_url = serve_pages(vec![(
+        "/apk/google-inc/youtube/youtube-19-02-34-release/",
+        release.to_string(),
+    )])
+    .await;
+
+    let apkmirror = test_client(&base_url).await;
+    let whats_new = apkmirror
+        .whats_new(&format!(
+            "{}/apk/google-inc/youtube/youtube-19-02-34-release/",
+            base_url
+        ))
+        .await
+        .unwrap();
+    assert_eq!(whats_new, "Bug fixes\nFaster startup");
+}
+
+#[tokio::test]
+async fn test_http_proxy() {
+    use tokio::net::TcpListener;
+
+    // Stands in for an HTTP proxy, returning the requests it forwarded
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = format!("http://user:secret@{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let mut requests = vec![];
+        for _ in 0..2 {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            respond(&mut socket, &request).await;
+            requests.push(request);
+        }
+        requests
+    });
+
+    let apkmirror = ApkMirror::builder()
+        .base_url("http://apkmirror.test")
+        .proxy(reqwest::Proxy::all(&proxy).unwrap())
+        .progress(progress::SilentReporter)
+        .build()
+        .await
+        .unwrap();
+    let dir = std::env::temp_dir().join(format!("downapk-proxy-test-{}", std::process::id()));
+    let file = apkmirror
+        .download_file(&proxy_test_item(), "com.example", dir.to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&file.path).unwrap(), "apk");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let requests = server.await.unwrap();
+    // "user:secret" in base64
+    let auth = "proxy-authorization: basic dxnlcjpzzwnyzxq=";
+    assert!(requests[0].starts_with("get http://apkmirror.test/ http/1.1"));
+    assert!(requests[0].contains(auth));
+    assert!(requests[1].starts_with("get http://files.test/app.apk http/1.1"));
+    assert!(requests[1].contains(auth));
+    // Files are downloaded without the headers of page requests
+    assert!(!requests[1].contains("x-requested-with"));
+}
+
+#[tokio::test]
+async fn test_socks5_proxy() {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // Stands in for a SOCKS5 proxy requiring a username and password,
+    // returning the requested host and the HTTP request it forwarded
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = format!("socks5h://user:secret@{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let mut requests = vec![];
+        for _ in 0..2 {
+            let (mut socket, _) = listener.accept().await.unwrap();
+
+            let mut greeting = [0; 2];
+            socket.read_exact(&mut greeting).await.unwrap();
+            let mut methods = vec![0; greeting[1] as usize];
+            socket.read_exact(&mut methods).await.unwrap();
+            assert!(methods.contains(&2), "Username/password method not offered");
+            socket.write_all(&[5, 2]).await.unwrap();
+
+            let mut auth = [0; 2];
+            socket.read_exact(&mut auth).await.unwrap();
+            let mut username = vec![0; auth[1] as usize];
+            socket.read_exact(&mut username).await.unwrap();
+            let mut len = [0; 1];
+            socket.read_exact(&mut len).await.unwrap();
+            let mut password = vec![0; len[0] as usize];
+            socket.read_exact(&mut password).await.unwrap();
+            assert_eq!(
+                (&username[..], &password[..]),
+                (&b"user"[..], &b"secret"[..])
+            );
+            socket.write_all(&[1, 0]).await.unwrap();
+
+            // CONNECT to a domain name, resolved by the proxy
+            let mut connect = [0; 5];
+            socket.read_exact(&mut connect).await.unwrap();
+            assert_eq!(connect[..4], [5, 1, 0, 3]);
+            let mut host = vec![0; connect[4] as usize + 2];
+            socket.read_exact(&mut host).await.unwrap();
+            socket
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 80])
+                .await
+                .unwrap();
+
+            let request = read_request(&mut socket).await;
+            respond(&mut socket, &request).await;
+            let port = u16::from_be_bytes([host[host.len() - 2], host[host.len() - 1]]);
+            host.truncate(host.len() - 2);
+            requests.push((String::from_utf8(host).unwrap(), port, request));
+        }
+        requests
+    });
+
+    let apkmirror = ApkMirror::builder()
+        .base_url("http://apkmirror.test")
+        .proxy(reqwest::Proxy::all(&proxy).unwrap())
+        .progress(progress::SilentReporter)
+        .build()
+        .await
+        .unwrap();
+    let dir = std::env::temp_dir().join(format!("downapk-socks-test-{}", std::process::id()));
+    let file = apkmirror
+        .download_file(&proxy_test_item(), "com.example", dir.to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&file.path).unwrap(), "apk");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let requests = server.await.unwrap();
+    let (host, port, request) = &requests[0];
+    assert_eq!((host.as_str(), *port), ("apkmirror.test", 80));
+    assert!(request.starts_with("get / http/1.1"));
+    let (host, port, request) = &requests[1];
+    assert_eq!((host.as_str(), *port), ("files.test", 80));
+    assert!(request.starts_with("get /app.apk http/1.1"));
+}
+
+/// Builds a client for the test server at `base_url`, without fetching the
+/// homepage or reporting progress.
+async fn test_client(base_url: &str) -> ApkMirror {
+    ApkMirror::builder()
+        .base_url(base_url)
+        .bootstrap(false)
+        .progress(progress::SilentReporter)
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Returns a row of a list of releases linking to `link`.
+fn row(link: &str, title: &str, version: &str) -> String {
+    format!(
+        "<div><a class='fontBlack' href='{}'>{} {}</a><div class='infoSlide t-height'><p>\
+         <span class='infoSlide-name'>Version:</span><span class='infoSlide-value'>{}</span></p></div></div>",
+        link, title, version, version
+    )
+}
+
+/// Returns the file details of a release or variant page showing `package`.
+fn package_page(package: &str) -> String {
+    format!(
+        "<div class='appspec-row'><svg><use xlink:href='#apkm-icon-file'></use></svg>\
+         <div class='appspec-value'>Version: 1.0<br>Package: {}<br>5 downloads</div></div>",
+        package
+    )
+}
+
+/// Serves the page of the first prefix matching the path of each request,
+/// or a 404 if none does, returning the base URL of the server.
+///
+/// The pages of a prefix listed several times are served in turn, the last
+/// one for every later request.
+async fn serve_pages(pages: Vec<(&'static str, String)>) -> String {
+    serve_logged_pages(pages).await.0
+}
+
+/// Like `serve_pages`, also returning the paths requested so far, lowercased.
+async fn serve_logged_pages(
+    pages: Vec<(&'static str, String)>,
+) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(std::sync::Mutex::new(vec![]));
+    let server_requests = requests.clone();
+    tokio::spawn(async move {
+        let mut pages = pages;
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let request = read_request(&mut socket).await;
+            let path = request.split(' ').nth(1).unwrap_or_default();
+            server_requests.lock().unwrap().push(path.to_string());
+            let index = pages
+                .iter()
+                .position(|(prefix, _)| path.starts_with(prefix));
+            let page = index.map(|index| {
+                match pages[index + 1..]
+                    .iter()
+                    .any(|(prefix, _)| *prefix == pages[index].0)
+                {
+                    true => pages.remove(index),
+                    false => pages[index].clone(),
+                }
+            });
+            let response = match page {
+                Some((_, body)) => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n<html>{}</html>",
+                    body.len() + "<html></html>".len(),
+                    body
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            };
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (base_url, requests)
+}
+
+/// Reads an HTTP request head from `socket`, lowercased.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    use tokio::io::AsyncReadExt;
+
+    let mut request = vec![];
+    let mut buf = [0; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let len = socket.read(&mut buf).await.unwrap();
+        assert!(len > 0, "Connection closed before the end of the request");
+        request.extend_from_slice(&buf[..len]);
+    }
+    String::from_utf8_lossy(&request).to_lowercase()
+}
+
+/// Answers `request` with a homepage with the search form, or a file body.
+async fn respond(socket: &mut tokio::net::TcpStream, request: &str) {
+    let body = match request.contains("/app.apk") {
+        true => "apk",
+        false => "<html><button class='searchButton'></button></html>",
+    };
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await.unwrap();
+}
+
+/// Returns a file answered by `respond`, on a host only reachable through a proxy.
+fn proxy_test_item() -> DownloadApkMirror {
+    DownloadApkMirror {
+        version: "1.0".to_string(),
+        download_link: "http://files.test/app.apk".to_string(),
+        variant_url: String::new(),
+        apk_type: ApkType::Apk,
+        arch: "universal".to_string(),
+        min_version: String::new(),
+        screen_dpi: "nodpi".to_string(),
+    }
+}
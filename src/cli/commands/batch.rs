@@ -0,0 +1,181 @@
+use super::{download_locked, download_release, newest_matching, search_entry};
+use crate::cli::args::BatchArgs;
+use crate::cli::config::Config;
+use crate::cli::error::CliError;
+use crate::cli::lockfile::{LockedFile, Lockfile};
+use crate::cli::manifest::{Manifest, ManifestEntry};
+use crate::cli::output::{print_list, OutputFormat, TableRow};
+use downapk::apkmirror::{ApkMirror, DownloadedFile};
+use serde::Serialize;
+
+/// Outcome of downloading one package of a batch.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchStatus {
+    Ok,
+    Failed,
+}
+
+/// Report of one package of a batch, printed once every package was processed.
+#[derive(Debug, Serialize)]
+pub struct BatchEntryReport {
+    /// The package the entry is about.
+    pub package_id: String,
+    /// Whether the package was downloaded.
+    pub status: BatchStatus,
+    /// The version of the downloaded release.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The downloaded files.
+    pub files: Vec<DownloadedFile>,
+    /// Why the package could not be downloaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TableRow for BatchEntryReport {
+    fn row(&self) -> String {
+        match self.status {
+            BatchStatus::Ok => format!(
+                "[ok] {} {} ({} files)",
+                self.package_id,
+                self.version.as_deref().unwrap_or_default(),
+                self.files.len()
+            ),
+            BatchStatus::Failed => format!(
+                "[failed] {}: {}",
+                self.package_id,
+                self.error.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// The manifest and lockfile of a batch, read before the client is built.
+pub struct BatchInputs {
+    args: BatchArgs,
+    manifest: Manifest,
+    lockfile: Lockfile,
+}
+
+impl BatchInputs {
+    /// Reads the manifest, filling the options its entries leave out from the
+    /// configuration, and the lockfile.
+    pub fn load(args: BatchArgs, config: &Config) -> Result<Self, CliError> {
+        let mut manifest = Manifest::load(&args.manifest)?;
+        config.apply_manifest(&mut manifest);
+        let lockfile = Lockfile::load(&args.lock.lockfile)?;
+        Ok(BatchInputs {
+            args,
+            manifest,
+            lockfile,
+        })
+    }
+}
+
+/// Downloads every package of a manifest with one `ApkMirror` session, carrying
+/// on with the next package when one fails.
+///
+/// The downloaded files are pinned in the lockfile, or verified against it with `--locked`.
+/// Each package is announced on stderr unless `quiet` is set.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    inputs: BatchInputs,
+    output: OutputFormat,
+    concurrency: usize,
+    save_challenge: Option<&str>,
+    quiet: bool,
+) -> Result<(), CliError> {
+    let BatchInputs {
+        args,
+        manifest,
+        mut lockfile,
+    } = inputs;
+
+    let mut reports = Vec::with_capacity(manifest.apps.len());
+    for entry in &manifest.apps {
+        if !quiet {
+            eprintln!("Processing {}", entry.package_id);
+        }
+        let output_dir = manifest.output_dir(entry);
+        let result = if args.lock.locked {
+            download_locked(apkmirror, &entry.package_id, &lockfile, output_dir)
+                .await
+                .map(|files| {
+                    let version = lockfile
+                        .package(&entry.package_id)
+                        .first()
+                        .map(|locked| locked.version.clone())
+                        .unwrap_or_default();
+                    (version, files)
+                })
+        } else {
+            download_entry(apkmirror, entry, output_dir, concurrency)
+                .await
+                .map(|(version, locked, files)| {
+                    lockfile.update(&entry.package_id, locked);
+                    (version, files)
+                })
+        };
+        let report = match result {
+            Ok((version, files)) => BatchEntryReport {
+                package_id: entry.package_id.clone(),
+                status: BatchStatus::Ok,
+                version: Some(version),
+                files,
+                error: None,
+            },
+            Err(e) => {
+                e.save_challenge(save_challenge);
+                BatchEntryReport {
+                    package_id: entry.package_id.clone(),
+                    status: BatchStatus::Failed,
+                    version: None,
+                    files: vec![],
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        reports.push(report);
+    }
+
+    if !args.lock.locked {
+        lockfile.save(&args.lock.lockfile)?;
+    }
+    print_list(output, &reports);
+
+    let failed = reports
+        .iter()
+        .filter(|report| matches!(report.status, BatchStatus::Failed))
+        .count();
+    match failed {
+        0 => Ok(()),
+        _ => Err(CliError::BatchFailed {
+            failed,
+            total: reports.len(),
+        }),
+    }
+}
+
+/// Downloads the variants of the newest release of an entry satisfying its constraints.
+///
+/// Returns the version of the release, the files to pin in the lockfile and the downloaded files.
+async fn download_entry(
+    apkmirror: &ApkMirror,
+    entry: &ManifestEntry,
+    output_dir: &str,
+    concurrency: usize,
+) -> Result<(String, Vec<LockedFile>, Vec<DownloadedFile>), CliError> {
+    let version_req = entry.version_req().map_err(CliError::InvalidInput)?;
+    let results = search_entry(apkmirror, entry, &version_req).await?;
+    let release = newest_matching(&results, &version_req).ok_or_else(|| {
+        CliError::NotFound(format!(
+            "No release of {} matching version {}",
+            entry.package_id, version_req
+        ))
+    })?;
+
+    let (locked, files) =
+        download_release(apkmirror, entry, release, output_dir, concurrency).await?;
+    Ok((release.version.clone(), locked, files))
+}
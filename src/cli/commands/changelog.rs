@@ -0,0 +1,78 @@
+use crate::cli::args::ChangelogArgs;
+use crate::cli::error::CliError;
+use crate::cli::output::{print_changelog, Changelog, ChangelogEntry, OutputFormat};
+use crate::cli::version::compare_versions;
+use downapk::apkmirror::{app_url, ApkMirror, ExtractedLink};
+use futures_util::{future, TryStreamExt};
+use std::cmp::Ordering;
+
+/// Checks that `--to` is not older than `--from`.
+pub fn check(args: &ChangelogArgs) -> Result<(), CliError> {
+    match &args.to {
+        Some(to) if compare_versions(to, &args.from) == Ordering::Less => Err(
+            CliError::InvalidInput(format!("--to {} is older than --from {}", to, args.from)),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Prints the "What's new" section of every release of a package between
+/// `--from` and `--to`, newest first.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    args: ChangelogArgs,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let app = apkmirror
+        .resolve_package(&args.package_id)
+        .await?
+        .ok_or_else(|| {
+            CliError::NotFound(format!("No app found with package {}", args.package_id))
+        })?;
+
+    // Results are ordered by upload date, so releases of an older track can be
+    // listed among newer ones and the whole crawl is filtered
+    let results = apkmirror
+        .search_pages(&args.package_id, args.max_pages)
+        .try_filter(|release| {
+            let in_range = app_url(&release.link).as_deref() == Some(app.as_str())
+                && in_range(&release.version, &args.from, args.to.as_deref());
+            future::ready(in_range)
+        });
+    let mut releases: Vec<ExtractedLink> = results.try_collect().await?;
+    releases.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    if releases.is_empty() {
+        return Err(CliError::NotFound(format!(
+            "No releases of {} found between {} and {}",
+            args.package_id,
+            args.from,
+            args.to.as_deref().unwrap_or("the latest version")
+        )));
+    }
+
+    let mut entries = vec![];
+    for release in releases {
+        entries.push(ChangelogEntry {
+            whats_new: apkmirror.whats_new(&release.link).await?,
+            version: release.version,
+            title: release.title,
+            uploaded: release.uploaded,
+            link: release.link,
+        });
+    }
+    print_changelog(
+        output,
+        &Changelog {
+            package_id: &args.package_id,
+            releases: entries,
+        },
+    );
+    Ok(())
+}
+
+/// Returns whether `version` is between `from` and `to`, both included, with
+/// no upper bound if `to` is not given.
+pub fn in_range(version: &str, from: &str, to: Option<&str>) -> bool {
+    compare_versions(version, from) != Ordering::Less
+        && to.is_none_or(|to| compare_versions(version, to) != Ordering::Greater)
+}
@@ -0,0 +1,34 @@
+use crate::cli::args::DeveloperArgs;
+use crate::cli::error::CliError;
+use crate::cli::output::{print_list, OutputFormat};
+use downapk::apkmirror::{developer_slug, ApkMirror};
+
+/// Checks that the developer is given as a slug or URL.
+pub fn check(args: &DeveloperArgs) -> Result<(), CliError> {
+    match developer_slug(&args.developer) {
+        Some(_) => Ok(()),
+        None => Err(CliError::InvalidInput(format!(
+            "{} is not a developer slug or URL",
+            args.developer
+        ))),
+    }
+}
+
+/// Prints the apps of a developer, most recently updated first.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    args: DeveloperArgs,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let apps = apkmirror
+        .developer_apps(&args.developer, args.max_pages)
+        .await?;
+    if apps.is_empty() {
+        return Err(CliError::NotFound(format!(
+            "No apps found for developer {}",
+            args.developer
+        )));
+    }
+    print_list(output, &apps);
+    Ok(())
+}
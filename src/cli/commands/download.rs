@@ -0,0 +1,168 @@
+use super::{app_releases, download_locked, pick, pick_release, search_app, search_releases};
+use crate::cli::args::{DownloadArgs, DownloadOption};
+use crate::cli::error::CliError;
+use crate::cli::lockfile::{LockedFile, Lockfile};
+use crate::cli::output::{print_report, DownloadReport, OutputFormat};
+use crate::cli::prompt::read_input;
+use downapk::apkmirror::ApkMirror;
+
+/// The arguments of `downapk download` with the lockfile they refer to, read
+/// before the client is built.
+pub struct DownloadInputs {
+    args: DownloadArgs,
+    lockfile: Lockfile,
+}
+
+impl DownloadInputs {
+    /// Checks the arguments and reads the lockfile.
+    pub fn load(args: DownloadArgs) -> Result<Self, CliError> {
+        if args.lock.locked && args.package_id.is_none() {
+            return Err(CliError::InvalidInput(
+                "--locked requires a package ID".to_string(),
+            ));
+        }
+        let lockfile = Lockfile::load(&args.lock.lockfile)?;
+        Ok(DownloadInputs { args, lockfile })
+    }
+}
+
+/// Downloads the variants of a release, found by searching the package ID
+/// unless the URL of a release, variant or download page is given.
+///
+/// The downloaded files are pinned in the lockfile, or verified against it with `--locked`.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    inputs: DownloadInputs,
+    output: OutputFormat,
+    concurrency: usize,
+) -> Result<(), CliError> {
+    let DownloadInputs { args, mut lockfile } = inputs;
+    let output_dir = args.output_dir().to_string();
+
+    if let (true, Some(package_id)) = (args.lock.locked, &args.package_id) {
+        let files = download_locked(apkmirror, package_id, &lockfile, &output_dir).await?;
+        print_report(
+            output,
+            &DownloadReport {
+                package_id,
+                files: &files,
+            },
+        );
+        return Ok(());
+    }
+
+    let (url, package_id) = match (args.url, args.package_id, args.name) {
+        (Some(url), package_id, _) => {
+            let package_id = package_id.unwrap_or_else(|| app_name_from_url(&url));
+            (url, package_id)
+        }
+        (None, Some(package_id), _) => {
+            let results =
+                search_releases(apkmirror, &package_id, &args.version_code, &args.results).await?;
+            let index = pick_release(&results, args.pick, args.search_index, output)?;
+            (results[index].link.clone(), package_id)
+        }
+        (None, None, Some(name)) => {
+            let app = search_app(apkmirror, &name, args.app_index, output).await?;
+            // Files are named after the app slug if its package ID is unknown
+            let package_id = app.package_id.clone().unwrap_or_else(|| app.slug.clone());
+            let results = app_releases(app, &args.version_code, &args.results)?;
+            let index = pick_release(&results, args.pick, args.search_index, output)?;
+            (results[index].link.clone(), package_id)
+        }
+        (None, None, None) => {
+            return Err(CliError::InvalidInput(
+                "Either a package ID, a name or a URL is required".to_string(),
+            ))
+        }
+    };
+
+    let download_result = apkmirror
+        .download_from_url(
+            &url,
+            args.filter.apk_type,
+            args.filter.arch(),
+            args.filter.dpi(),
+        )
+        .await?;
+
+    let download_all = match download_result.len() {
+        0 => {
+            return Err(CliError::NotFound(
+                "No apk files found for download. Retry again after some time".to_string(),
+            ));
+        }
+        1 => true,
+        _ => match args.download_option {
+            Some(DownloadOption::All) => true,
+            Some(DownloadOption::One) => false,
+            None => {
+                eprintln!("There are multiple apk files available for download");
+                eprintln!("1. Download one specific file");
+                eprintln!("2. Download all files");
+                match read_input("Choose a number from above:")? {
+                    1 => false,
+                    2 => true,
+                    _ => {
+                        return Err(CliError::InvalidInput(
+                            "Invalid choice. Choose either 1 or 2".to_string(),
+                        ))
+                    }
+                }
+            }
+        },
+    };
+
+    eprintln!();
+    let (items, files) = if download_all {
+        let files = apkmirror
+            .download_files(&download_result, &package_id, &output_dir, concurrency)
+            .await?;
+        (&download_result[..], files)
+    } else {
+        let index = pick(
+            &download_result,
+            args.download_index,
+            output,
+            "download index",
+        )?;
+        let file = apkmirror
+            .download_file(&download_result[index], &package_id, &output_dir)
+            .await?;
+        (&download_result[index..=index], vec![file])
+    };
+
+    lockfile.update(
+        &package_id,
+        items
+            .iter()
+            .zip(&files)
+            .map(|(item, file)| LockedFile::new(&package_id, item, file))
+            .collect(),
+    );
+    lockfile.save(&args.lock.lockfile)?;
+
+    print_report(
+        output,
+        &DownloadReport {
+            package_id: &package_id,
+            files: &files,
+        },
+    );
+
+    Ok(())
+}
+
+/// Returns the app slug of an ApkMirror URL such as
+/// `https://www.apkmirror.com/apk/<developer>/<app>/<release>/`, used to name
+/// files when no package ID is given.
+fn app_name_from_url(url: &str) -> String {
+    let mut segments = url
+        .split('/')
+        .skip_while(|segment| *segment != "apk")
+        .skip(2);
+    match segments.next() {
+        Some(app) if !app.is_empty() => app.to_string(),
+        _ => "download".to_string(),
+    }
+}
@@ -0,0 +1,24 @@
+use super::{pick_release, search_releases};
+use crate::cli::args::InfoArgs;
+use crate::cli::error::CliError;
+use crate::cli::output::{print_details, OutputFormat};
+use downapk::apkmirror::ApkMirror;
+
+/// Prints the details of a release of a package, scraped from its release and variant pages.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    args: InfoArgs,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let results = search_releases(
+        apkmirror,
+        &args.package_id,
+        &args.version_code,
+        &args.results,
+    )
+    .await?;
+    let index = pick_release(&results, args.pick, args.search_index, output)?;
+    let release = apkmirror.release_info(&results[index].link).await?;
+    print_details(output, &release);
+    Ok(())
+}
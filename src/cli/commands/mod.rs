@@ -0,0 +1,402 @@
+/// `downapk batch`
+pub mod batch;
+/// `downapk changelog`
+pub mod changelog;
+/// `downapk developer`
+pub mod developer;
+/// `downapk download`
+pub mod download;
+/// `downapk info`
+pub mod info;
+/// `downapk search`
+pub mod search;
+/// `downapk variants`
+pub mod variants;
+/// `downapk watch` and `downapk check-updates`
+pub mod watch;
+
+use super::args::{
+    ChangelogArgs, Cli, Command, DeveloperArgs, DownloadArgs, InfoArgs, PickPolicy, ProgressMode,
+    ResultArgs, SearchArgs, VariantsArgs,
+};
+use super::config::Config;
+use super::error::CliError;
+use super::lockfile::{LockedFile, Lockfile};
+use super::manifest::ManifestEntry;
+use super::output::{print_list, OutputFormat, TableRow};
+use super::prompt::{check_index, read_input};
+use super::version::{compare_versions, VersionReq};
+use downapk::apkmirror::{ApkMirror, AppMatch, DownloadedFile, ExtractedLink};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// Runs the subcommand given on the command line, `download` if none was given.
+///
+/// Options not given on the command line are read from the configuration.
+pub async fn run(mut cli: Cli) -> Result<(), CliError> {
+    let config = Config::load()?;
+    config.apply_global(&mut cli.global);
+
+    let output = cli.global.output;
+    let save_challenge = cli.global.save_challenge.as_deref();
+    let concurrency = cli.global.concurrency();
+    let quiet = cli.global.progress_mode() == ProgressMode::None;
+    // Invalid input fails before the client contacts the website
+    let task = Task::prepare(cli.command, cli.download, &config)?;
+    let apkmirror = cli.global.apkmirror_builder()?.build().await?;
+
+    let result = match task {
+        Task::Search(args) => search::run(&apkmirror, args, output).await,
+        Task::Variants(args) => variants::run(&apkmirror, args, output).await,
+        Task::Download(inputs) => download::run(&apkmirror, inputs, output, concurrency).await,
+        Task::Info(args) => info::run(&apkmirror, args, output).await,
+        Task::Changelog(args) => changelog::run(&apkmirror, args, output).await,
+        Task::Developer(args) => developer::run(&apkmirror, args, output).await,
+        Task::Batch(inputs) => {
+            batch::run(
+                &apkmirror,
+                inputs,
+                output,
+                concurrency,
+                save_challenge,
+                quiet,
+            )
+            .await
+        }
+        Task::Watch { inputs, download } => {
+            let options = watch::WatchOptions {
+                download,
+                concurrency,
+                save_challenge,
+                quiet,
+            };
+            watch::run(&apkmirror, inputs, output, options).await
+        }
+    };
+
+    // Keep the cookies refreshed during the run for the next one
+    if let Err(e) = apkmirror.save_cookies() {
+        eprintln!("Warning: {}", e);
+    }
+    result
+}
+
+/// A subcommand whose arguments were checked and whose input files were read.
+enum Task {
+    Search(SearchArgs),
+    Variants(VariantsArgs),
+    Download(download::DownloadInputs),
+    Info(InfoArgs),
+    Changelog(ChangelogArgs),
+    Developer(DeveloperArgs),
+    Batch(batch::BatchInputs),
+    /// `downapk watch`, or `downapk check-updates` if `download` is not set.
+    Watch {
+        inputs: watch::WatchInputs,
+        download: bool,
+    },
+}
+
+impl Task {
+    /// Checks the arguments of the subcommand, `download` if none was given,
+    /// and reads the files it needs.
+    fn prepare(
+        command: Option<Command>,
+        download: DownloadArgs,
+        config: &Config,
+    ) -> Result<Self, CliError> {
+        let task = match command {
+            Some(Command::Search(args)) => Task::Search(args),
+            Some(Command::Variants(mut args)) => {
+                config.apply_filter(&mut args.filter);
+                Task::Variants(args)
+            }
+            Some(Command::Download(args)) => {
+                Task::Download(download::DownloadInputs::load(configure(config, args))?)
+            }
+            Some(Command::Info(args)) => Task::Info(args),
+            Some(Command::Changelog(args)) => {
+                changelog::check(&args)?;
+                Task::Changelog(args)
+            }
+            Some(Command::Developer(args)) => {
+                developer::check(&args)?;
+                Task::Developer(args)
+            }
+            Some(Command::Batch(args)) => Task::Batch(batch::BatchInputs::load(args, config)?),
+            Some(Command::Watch(args)) => Task::Watch {
+                inputs: watch::WatchInputs::load(args, config)?,
+                download: true,
+            },
+            Some(Command::CheckUpdates(args)) => Task::Watch {
+                inputs: watch::WatchInputs::load(args, config)?,
+                download: false,
+            },
+            None => Task::Download(download::DownloadInputs::load(configure(config, download))?),
+        };
+        Ok(task)
+    }
+}
+
+/// Fills the options of `downapk download` not given on the command line.
+fn configure(config: &Config, mut args: DownloadArgs) -> DownloadArgs {
+    config.apply_filter(&mut args.filter);
+    config.apply_output_dir(&mut args.output_dir);
+    args
+}
+
+/// Searches releases of the app with the package ID `package_id`, restricted to
+/// `version_code` unless it is `latest`, then filters and sorts them as requested by `options`.
+///
+/// Returns `CliError::NotFound` if nothing matched.
+pub async fn search_releases(
+    apkmirror: &ApkMirror,
+    package_id: &str,
+    version_code: &str,
+    options: &ResultArgs,
+) -> Result<Vec<ExtractedLink>, CliError> {
+    let version = match version_code {
+        "latest" => None,
+        version => Some(version),
+    };
+    let results = match (options.no_resolve, version) {
+        (false, version) => apkmirror.search_package(package_id, version).await?,
+        (true, None) => apkmirror.search(package_id).await?,
+        (true, Some(version)) => apkmirror.search_by_version(package_id, version).await?,
+    };
+
+    if results.is_empty() {
+        let hint = match options.no_resolve {
+            false => ". Pass --no-resolve to list the releases of every app matching it",
+            true => "",
+        };
+        return Err(CliError::NotFound(format!(
+            "No results found for {}{}",
+            package_id, hint
+        )));
+    }
+    let results = options.apply(results);
+    if results.is_empty() {
+        return Err(CliError::NotFound(format!(
+            "No results found for {} matching the filters",
+            package_id
+        )));
+    }
+    Ok(results)
+}
+
+/// Searches apps named like `name` and returns the one picked by the 1-based
+/// `index`, listing the apps and prompting for one if `index` is not given.
+pub async fn search_app(
+    apkmirror: &ApkMirror,
+    name: &str,
+    index: Option<usize>,
+    output: OutputFormat,
+) -> Result<AppMatch, CliError> {
+    let mut apps = apkmirror.search_apps(name).await?;
+    if apps.is_empty() {
+        return Err(CliError::NotFound(format!("No apps found for {}", name)));
+    }
+    let index = pick(&apps, index, output, "app index")?;
+    Ok(apps.swap_remove(index))
+}
+
+/// Returns the releases of `app` listed by the name search, restricted to
+/// `version_code` unless it is `latest`, then filtered and sorted as requested by `options`.
+///
+/// Returns `CliError::NotFound` if nothing matched.
+pub fn app_releases(
+    app: AppMatch,
+    version_code: &str,
+    options: &ResultArgs,
+) -> Result<Vec<ExtractedLink>, CliError> {
+    let mut results = app.releases;
+    if version_code != "latest" {
+        results.retain(|result| result.version == version_code);
+    }
+    let results = options.apply(results);
+    if results.is_empty() {
+        return Err(CliError::NotFound(format!(
+            "No results found for {} matching the filters",
+            app.name
+        )));
+    }
+    Ok(results)
+}
+
+/// Returns the 0-based index of the release picked by `policy`, or like `pick`
+/// with the 1-based `index` if there is no policy.
+pub fn pick_release(
+    results: &[ExtractedLink],
+    policy: Option<PickPolicy>,
+    index: Option<usize>,
+    output: OutputFormat,
+) -> Result<usize, CliError> {
+    match policy {
+        Some(policy) => Ok(policy.pick(results)),
+        None => pick(results, index, output, "search index"),
+    }
+}
+
+/// Returns the 0-based index of the item picked by the 1-based `index`, listing the
+/// items and prompting for one if `index` is not given.
+pub fn pick<T: Serialize + TableRow>(
+    items: &[T],
+    index: Option<usize>,
+    output: OutputFormat,
+    what: &str,
+) -> Result<usize, CliError> {
+    let choice = match index {
+        Some(choice) => choice,
+        None => {
+            print_list(output, items);
+            read_input("Choose a number from above:")?
+        }
+    };
+    check_index(choice, items.len(), what)
+}
+
+/// Returns the release with the highest version satisfying `version_req`,
+/// the first one listed if several share that version.
+pub fn newest_matching<'a>(
+    results: &'a [ExtractedLink],
+    version_req: &VersionReq,
+) -> Option<&'a ExtractedLink> {
+    results
+        .iter()
+        .filter(|result| version_req.matches(&result.version))
+        .reduce(
+            |newest, result| match compare_versions(&result.version, &newest.version) {
+                Ordering::Greater => result,
+                _ => newest,
+            },
+        )
+}
+
+/// Searches the releases of the app of a manifest entry satisfying its version constraint.
+///
+/// Versions are compared numerically, so that `=19.2.34` finds `19.02.34`.
+pub async fn search_entry(
+    apkmirror: &ApkMirror,
+    entry: &ManifestEntry,
+    version_req: &VersionReq,
+) -> Result<Vec<ExtractedLink>, CliError> {
+    let mut results = apkmirror.search_package(&entry.package_id, None).await?;
+    results.retain(|result| version_req.matches(&result.version));
+    Ok(results)
+}
+
+/// Downloads the variants of `release` matching the filters of a manifest entry.
+///
+/// Returns the files to pin in the lockfile and the downloaded files.
+pub async fn download_release(
+    apkmirror: &ApkMirror,
+    entry: &ManifestEntry,
+    release: &ExtractedLink,
+    output_dir: &str,
+    concurrency: usize,
+) -> Result<(Vec<LockedFile>, Vec<DownloadedFile>), CliError> {
+    let apk_type = entry.apk_type().map_err(CliError::InvalidInput)?;
+    let variants = apkmirror
+        .download_from_url(&release.link, apk_type, entry.arch(), entry.dpi())
+        .await?;
+    if variants.is_empty() {
+        return Err(CliError::NotFound(format!(
+            "No apk files matching the filters found in {}",
+            release.link
+        )));
+    }
+
+    let files = apkmirror
+        .download_files(&variants, &entry.package_id, output_dir, concurrency)
+        .await?;
+    let locked = variants
+        .iter()
+        .zip(&files)
+        .map(|(item, file)| LockedFile::new(&entry.package_id, item, file))
+        .collect();
+    Ok((locked, files))
+}
+
+/// Downloads exactly the files of a package pinned in the lockfile, failing
+/// if a variant is gone or a file differs from the lockfile.
+pub async fn download_locked(
+    apkmirror: &ApkMirror,
+    package_id: &str,
+    lockfile: &Lockfile,
+    output_dir: &str,
+) -> Result<Vec<DownloadedFile>, CliError> {
+    let locked_files = lockfile.package(package_id);
+    if locked_files.is_empty() {
+        return Err(CliError::Locked(format!(
+            "{} is not in the lockfile",
+            package_id
+        )));
+    }
+
+    // Files are downloaded into a staging directory and only moved into the output
+    // directory once all of them match, so that a mismatch never replaces a verified copy
+    let staging = Path::new(output_dir).join(format!(".downapk-locked-{}", std::process::id()));
+    let result = download_verified(apkmirror, package_id, &locked_files, &staging).await;
+    let result = match result {
+        Ok(files) => move_files(files, output_dir).await,
+        Err(e) => Err(e),
+    };
+    // The staging directory only holds files that are not needed anymore
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+    result
+}
+
+/// Downloads the pinned files of a package into `dir`, failing if a variant
+/// is gone or a file differs from the lockfile.
+async fn download_verified(
+    apkmirror: &ApkMirror,
+    package_id: &str,
+    locked_files: &[&LockedFile],
+    dir: &Path,
+) -> Result<Vec<DownloadedFile>, CliError> {
+    let dir = dir.to_string_lossy();
+    let mut files = Vec::with_capacity(locked_files.len());
+    for locked in locked_files {
+        // The variant page URL identifies the file, its content is verified by the hash below
+        let item = apkmirror
+            .download_from_url(&locked.variant_url, None, None, None)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                CliError::Locked(format!("{} no longer offers a file", locked.variant_url))
+            })?;
+        if let Some(mismatch) = locked.mismatch(&item) {
+            return Err(CliError::Locked(mismatch));
+        }
+
+        let file = apkmirror.download_file(&item, package_id, &dir).await?;
+        if file.size != locked.size || file.sha256 != locked.sha256 {
+            return Err(CliError::Locked(format!(
+                "{} differs from the lockfile: expected {} bytes with sha256 {}, got {} bytes with sha256 {}",
+                locked.variant_url,
+                locked.size,
+                locked.sha256,
+                file.size,
+                file.sha256
+            )));
+        }
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Moves downloaded files into `output_dir`, replacing the files of the same name.
+async fn move_files(
+    mut files: Vec<DownloadedFile>,
+    output_dir: &str,
+) -> Result<Vec<DownloadedFile>, CliError> {
+    for file in &mut files {
+        let path = Path::new(output_dir).join(file.path.file_name().unwrap_or_default());
+        tokio::fs::rename(&file.path, &path).await?;
+        file.path = path;
+    }
+    Ok(files)
+}
@@ -0,0 +1,36 @@
+use super::{app_releases, search_releases};
+use crate::cli::args::SearchArgs;
+use crate::cli::error::CliError;
+use crate::cli::output::{print_list, OutputFormat};
+use crate::cli::prompt::check_index;
+use downapk::apkmirror::ApkMirror;
+
+/// Prints the releases of a package matching the requested version, or the
+/// apps matching a name and, once one is chosen, its releases.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    args: SearchArgs,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let results = match (args.package_id, args.name) {
+        (Some(package_id), _) => {
+            search_releases(apkmirror, &package_id, &args.version_code, &args.results).await?
+        }
+        (None, Some(name)) => {
+            let mut apps = apkmirror.search_apps(&name).await?;
+            let Some(index) = args.app_index else {
+                print_list(output, &apps);
+                return Ok(());
+            };
+            let index = check_index(index, apps.len(), "app index")?;
+            app_releases(apps.swap_remove(index), &args.version_code, &args.results)?
+        }
+        (None, None) => {
+            return Err(CliError::InvalidInput(
+                "Either a package ID or a name is required".to_string(),
+            ))
+        }
+    };
+    print_list(output, &results);
+    Ok(())
+}
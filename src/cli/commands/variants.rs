@@ -0,0 +1,29 @@
+use crate::cli::args::VariantsArgs;
+use crate::cli::error::CliError;
+use crate::cli::output::{print_list, OutputFormat};
+use downapk::apkmirror::ApkMirror;
+
+/// Prints the downloadable variants of a release, variant or download page matching the filters.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    args: VariantsArgs,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let variants = apkmirror
+        .download_from_url(
+            &args.url,
+            args.filter.apk_type,
+            args.filter.arch(),
+            args.filter.dpi(),
+        )
+        .await?;
+
+    if variants.is_empty() {
+        return Err(CliError::NotFound(format!(
+            "No apk files matching the filters found in {}",
+            args.url
+        )));
+    }
+    print_list(output, &variants);
+    Ok(())
+}
@@ -0,0 +1,368 @@
+use super::{download_release, newest_matching, search_entry};
+use crate::cli::args::WatchArgs;
+use crate::cli::config::Config;
+use crate::cli::error::CliError;
+use crate::cli::lockfile::Lockfile;
+use crate::cli::manifest::{Manifest, ManifestEntry};
+use crate::cli::output::{print_list, ChangelogEntry, OutputFormat, TableRow};
+use crate::cli::state::WatchState;
+use crate::cli::version::{compare_versions, VersionReq};
+use downapk::apkmirror::{ApkMirror, DownloadedFile, ExtractedLink};
+use downapk::errors::DownApkError;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// Outcome of checking one tracked package for updates.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchStatus {
+    /// No version newer than the last downloaded one.
+    UpToDate,
+    /// A newer version exists but was not downloaded.
+    Available,
+    /// A newer version was downloaded.
+    Updated,
+    /// The package could not be checked or downloaded.
+    Failed,
+}
+
+/// Report of one tracked package, listing what changed since the last run.
+#[derive(Debug, Serialize)]
+pub struct WatchReport {
+    /// The package the entry is about.
+    pub package_id: String,
+    /// The outcome of the check.
+    pub status: WatchStatus,
+    /// The last downloaded version before this run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    /// The newest version matching the constraints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+    /// The versions uploaded since the last downloaded one, oldest first.
+    pub new_versions: Vec<String>,
+    /// The changes of the new versions, oldest first, or only of the newest
+    /// one if nothing was downloaded before.
+    pub changes: Vec<ChangelogEntry>,
+    /// The downloaded files.
+    pub files: Vec<DownloadedFile>,
+    /// Why the package could not be checked or downloaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TableRow for WatchReport {
+    fn row(&self) -> String {
+        let previous = self.previous.as_deref().unwrap_or("none");
+        let latest = self.latest.as_deref().unwrap_or_default();
+        let summary = match self.status {
+            WatchStatus::UpToDate => format!("[up-to-date] {} {}", self.package_id, previous),
+            WatchStatus::Available => format!(
+                "[available] {} {} -> {} (new: {})",
+                self.package_id,
+                previous,
+                latest,
+                self.new_versions.join(", ")
+            ),
+            WatchStatus::Updated => format!(
+                "[updated] {} {} -> {} (new: {}), {} files",
+                self.package_id,
+                previous,
+                latest,
+                self.new_versions.join(", "),
+                self.files.len()
+            ),
+            WatchStatus::Failed => format!(
+                "[failed] {}: {}",
+                self.package_id,
+                self.error.as_deref().unwrap_or_default()
+            ),
+        };
+        self.changes
+            .iter()
+            .filter(|change| !change.whats_new.is_empty())
+            .fold(summary, |row, change| {
+                let lines: Vec<String> = change
+                    .whats_new
+                    .lines()
+                    .map(|line| format!("    {}", line))
+                    .collect();
+                format!("{}\n  {}:\n{}", row, change.version, lines.join("\n"))
+            })
+    }
+}
+
+/// A release of a tracked package newer than the last downloaded one.
+struct Update {
+    /// The newest release matching the constraints.
+    release: ExtractedLink,
+    /// The versions uploaded since the last downloaded one, oldest first.
+    new_versions: Vec<String>,
+    /// The changes of the new versions, oldest first.
+    changes: Vec<ChangelogEntry>,
+}
+
+/// How `run` handles the packages with a new version.
+pub struct WatchOptions<'a> {
+    /// Whether the new versions are downloaded or only reported.
+    pub download: bool,
+    /// How many files are downloaded at the same time.
+    pub concurrency: usize,
+    /// Directory to save the HTML of challenge pages into.
+    pub save_challenge: Option<&'a str>,
+    /// Whether the package being checked is not announced, as progress is off.
+    pub quiet: bool,
+}
+
+/// The manifest, state file and lockfile of a watch run, read before the client is built.
+pub struct WatchInputs {
+    args: WatchArgs,
+    manifest: Manifest,
+    state: WatchState,
+    lockfile: Lockfile,
+}
+
+impl WatchInputs {
+    /// Reads the manifest, filling the options its entries leave out from the
+    /// configuration, the state file and the lockfile.
+    pub fn load(args: WatchArgs, config: &Config) -> Result<Self, CliError> {
+        let mut manifest = Manifest::load(&args.manifest)?;
+        config.apply_manifest(&mut manifest);
+        let state = WatchState::load(&args.state)?;
+        let lockfile = Lockfile::load(&args.lockfile)?;
+        Ok(WatchInputs {
+            args,
+            manifest,
+            state,
+            lockfile,
+        })
+    }
+}
+
+/// Checks every package of a manifest for a version newer than the last one
+/// recorded in the state file, downloading it if `options.download` is set.
+pub async fn run(
+    apkmirror: &ApkMirror,
+    inputs: WatchInputs,
+    output: OutputFormat,
+    options: WatchOptions<'_>,
+) -> Result<(), CliError> {
+    let WatchOptions {
+        download,
+        concurrency,
+        save_challenge,
+        quiet,
+    } = options;
+    let WatchInputs {
+        args,
+        manifest,
+        mut state,
+        mut lockfile,
+    } = inputs;
+    let resolved_apps = state.apps.len();
+
+    let mut reports = Vec::with_capacity(manifest.apps.len());
+    for entry in &manifest.apps {
+        if !quiet {
+            eprintln!("Checking {}", entry.package_id);
+        }
+        let previous = state.version(&entry.package_id).map(str::to_string);
+        let mut report = WatchReport {
+            package_id: entry.package_id.clone(),
+            status: WatchStatus::UpToDate,
+            previous: previous.clone(),
+            latest: None,
+            new_versions: vec![],
+            changes: vec![],
+            files: vec![],
+            error: None,
+        };
+
+        let result = match check_entry(apkmirror, entry, previous.as_deref(), &mut state).await {
+            Ok(Some(Update {
+                release,
+                new_versions,
+                changes,
+            })) => {
+                report.latest = Some(release.version.clone());
+                report.new_versions = new_versions;
+                report.changes = changes;
+                report.status = WatchStatus::Available;
+                if download {
+                    let output_dir = manifest.output_dir(entry);
+                    download_release(apkmirror, entry, &release, output_dir, concurrency)
+                        .await
+                        .map(|(locked, files)| {
+                            lockfile.update(&entry.package_id, locked);
+                            state.record(&entry.package_id, &release.version);
+                            report.files = files;
+                            report.status = WatchStatus::Updated;
+                        })
+                } else {
+                    Ok(())
+                }
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            e.save_challenge(save_challenge);
+            report.status = WatchStatus::Failed;
+            report.error = Some(e.to_string());
+        }
+        reports.push(report);
+    }
+
+    if download {
+        lockfile.save(&args.lockfile)?;
+    }
+    // Checking without downloading still remembers the apps resolved on the way
+    if download || state.apps.len() != resolved_apps {
+        state.save(&args.state)?;
+    }
+    print_list(output, &reports);
+
+    let failed = reports
+        .iter()
+        .filter(|report| matches!(report.status, WatchStatus::Failed))
+        .count();
+    match failed {
+        0 => Ok(()),
+        _ => Err(CliError::BatchFailed {
+            failed,
+            total: reports.len(),
+        }),
+    }
+}
+
+/// Looks for a release of an entry newer than `previous`, reading the feed of
+/// its app and only searching if the feed lists no matching release.
+///
+/// Returns the newest matching release with the versions uploaded since
+/// `previous` and their changes, or `None` if there is nothing new. Without
+/// `previous` only the changes of the newest release are read, so that the
+/// first run does not fetch the release page of every listed version.
+async fn check_entry(
+    apkmirror: &ApkMirror,
+    entry: &ManifestEntry,
+    previous: Option<&str>,
+    state: &mut WatchState,
+) -> Result<Option<Update>, CliError> {
+    let version_req = entry.version_req().map_err(CliError::InvalidInput)?;
+    let results = match feed_releases(apkmirror, entry, &version_req, state).await? {
+        Some(results) => results,
+        None => search_entry(apkmirror, entry, &version_req).await?,
+    };
+
+    let release = match newest_matching(&results, &version_req) {
+        Some(release) => release,
+        None => {
+            return Err(CliError::NotFound(format!(
+                "No release of {} matching version {}",
+                entry.package_id, version_req
+            )))
+        }
+    };
+    if previous
+        .is_some_and(|previous| compare_versions(&release.version, previous) != Ordering::Greater)
+    {
+        return Ok(None);
+    }
+
+    let versions = results.iter().map(|result| result.version.as_str());
+    let new_versions = new_versions(versions, &version_req, previous);
+    let described = match previous {
+        Some(_) => new_versions.as_slice(),
+        None => std::slice::from_ref(&release.version),
+    };
+    let mut changes = Vec::with_capacity(described.len());
+    for version in described {
+        let Some(result) = results
+            .iter()
+            .find(|result| compare_versions(&result.version, version) == Ordering::Equal)
+        else {
+            continue;
+        };
+        changes.push(ChangelogEntry {
+            version: result.version.clone(),
+            title: result.title.clone(),
+            uploaded: result.uploaded.clone(),
+            link: result.link.clone(),
+            whats_new: apkmirror.whats_new(&result.link).await?,
+        });
+    }
+
+    Ok(Some(Update {
+        release: release.clone(),
+        new_versions,
+        changes,
+    }))
+}
+
+/// Reads the latest releases of an entry from the feed of its app, resolving
+/// the app page on the first run and remembering it in the state file.
+///
+/// Returns `None` if no app has the package ID, the feed could not be parsed
+/// or it lists no release satisfying `version_req`, such as a version older
+/// than the feed goes back.
+async fn feed_releases(
+    apkmirror: &ApkMirror,
+    entry: &ManifestEntry,
+    version_req: &VersionReq,
+    state: &mut WatchState,
+) -> Result<Option<Vec<ExtractedLink>>, CliError> {
+    let app = match state.app_url(&entry.package_id) {
+        Some(app) => app.to_string(),
+        None => {
+            let Some(app) = apkmirror.resolve_package(&entry.package_id).await? else {
+                return Ok(None);
+            };
+            state.record_app(&entry.package_id, &app);
+            app
+        }
+    };
+    let entries = match apkmirror.app_feed(&app).await {
+        Ok(entries) => entries,
+        // No feed, e.g. the app moved, so resolve it again on the next run
+        Err(e) if not_a_feed(&e) => {
+            state.apps.remove(&entry.package_id);
+            return Ok(None);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let results: Vec<ExtractedLink> = entries.into_iter().map(ExtractedLink::from).collect();
+    let matching = results
+        .iter()
+        .any(|result| version_req.matches(&result.version));
+    Ok(matching.then_some(results))
+}
+
+/// Returns whether reading a feed failed because there is no feed at the URL,
+/// rather than because of the network or a challenge page.
+fn not_a_feed(error: &DownApkError<'static>) -> bool {
+    match error {
+        DownApkError::Other(_) => true,
+        DownApkError::Reqwest(e) => e.status() == Some(StatusCode::NOT_FOUND),
+        _ => false,
+    }
+}
+
+/// Returns the distinct versions satisfying `version_req` that are newer than
+/// `previous`, oldest first.
+pub fn new_versions<'a>(
+    versions: impl IntoIterator<Item = &'a str>,
+    version_req: &VersionReq,
+    previous: Option<&str>,
+) -> Vec<String> {
+    let mut versions: Vec<&str> = versions
+        .into_iter()
+        .filter(|version| version_req.matches(version))
+        .filter(|version| {
+            previous.is_none_or(|previous| compare_versions(version, previous) == Ordering::Greater)
+        })
+        .collect();
+    versions.sort_by(|a, b| compare_versions(a, b));
+    versions.dedup_by(|a, b| compare_versions(a, b) == Ordering::Equal);
+    versions.into_iter().map(str::to_string).collect()
+}
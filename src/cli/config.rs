@@ -0,0 +1,226 @@
+use super::args::{FilterArgs, GlobalArgs, ProgressMode};
+use super::error::CliError;
+use super::manifest::Manifest;
+use clap::ValueEnum;
+use downapk::apkmirror::cache::cache_dir;
+use downapk::apkmirror::progress::{LogReporter, SilentReporter};
+use downapk::apkmirror::{ApkMirror, ApkMirrorBuilder, ApkType, PageCache};
+use reqwest::Proxy;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Name of the project-local configuration file, looked up in the working directory.
+pub const PROJECT_CONFIG_FILE: &str = "downapk.toml";
+
+/// Default output directory when neither the configuration nor the command line sets one.
+pub const DEFAULT_OUTPUT_DIR: &str = "downloads";
+
+/// Default time to live of cached pages, in seconds.
+pub const DEFAULT_CACHE_TTL: u64 = 600;
+
+/// Defaults of the command line options, e.g.
+///
+/// ```toml
+/// output_dir = "apks"
+/// arch = "arm64-v8a"
+/// dpi = "nodpi"
+/// apk_type = "apk"
+/// proxy = "http://127.0.0.1:8080"
+/// retries = 3
+/// concurrency = 4
+/// cache_ttl = 600
+/// ```
+///
+/// Read in order from `$XDG_CONFIG_HOME/downapk/config.toml`, `downapk.toml` in
+/// the working directory and `DOWNAPK_*` environment variables, each layer
+/// overriding the previous ones. Command line flags override all of them.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Directory to download files into.
+    pub output_dir: Option<String>,
+    /// Preferred architecture.
+    pub arch: Option<String>,
+    /// Preferred screen DPI.
+    pub dpi: Option<String>,
+    /// Preferred type of APK (`apk` or `bundle`).
+    pub apk_type: Option<String>,
+    /// Proxy to send requests through.
+    pub proxy: Option<String>,
+    /// How many times a page request failing with a network error is retried.
+    pub retries: Option<u32>,
+    /// How many files are downloaded at the same time.
+    pub concurrency: Option<usize>,
+    /// How long scraped pages are cached, in seconds. `0` disables the cache.
+    pub cache_ttl: Option<u64>,
+}
+
+impl Config {
+    /// Reads every configuration layer, later layers overriding earlier ones.
+    pub fn load() -> Result<Self, CliError> {
+        let mut config = Config::default();
+        if let Some(path) = user_config_path() {
+            config = config.merge(Self::load_file(&path)?);
+        }
+        config = config.merge(Self::load_file(Path::new(PROJECT_CONFIG_FILE))?);
+        Ok(config.merge(Self::from_env(std::env::vars())?))
+    }
+
+    /// Reads the configuration file at `path`, returning an empty configuration if it does not exist.
+    fn load_file(path: &Path) -> Result<Self, CliError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => Err(e)?,
+        };
+        Self::parse(&content)
+            .map_err(|e| CliError::InvalidInput(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses and validates a configuration file.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(content).map_err(|e| e.to_string())?;
+        config.apk_type()?;
+        Ok(config)
+    }
+
+    /// Reads the `DOWNAPK_*` variables among `vars`.
+    pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self, CliError> {
+        let mut config = Config::default();
+        for (key, value) in vars {
+            let Some(key) = key.strip_prefix("DOWNAPK_") else {
+                continue;
+            };
+            match key {
+                "OUTPUT_DIR" => config.output_dir = Some(value),
+                "ARCH" => config.arch = Some(value),
+                "DPI" => config.dpi = Some(value),
+                "APK_TYPE" => config.apk_type = Some(value),
+                "PROXY" => config.proxy = Some(value),
+                "RETRIES" => config.retries = Some(parse_env(key, &value)?),
+                "CONCURRENCY" => config.concurrency = Some(parse_env(key, &value)?),
+                "CACHE_TTL" => config.cache_ttl = Some(parse_env(key, &value)?),
+                _ => {}
+            }
+        }
+        config.apk_type().map_err(CliError::InvalidInput)?;
+        Ok(config)
+    }
+
+    /// Returns this configuration with the values set in `other` overriding its own.
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            output_dir: other.output_dir.or(self.output_dir),
+            arch: other.arch.or(self.arch),
+            dpi: other.dpi.or(self.dpi),
+            apk_type: other.apk_type.or(self.apk_type),
+            proxy: other.proxy.or(self.proxy),
+            retries: other.retries.or(self.retries),
+            concurrency: other.concurrency.or(self.concurrency),
+            cache_ttl: other.cache_ttl.or(self.cache_ttl),
+        }
+    }
+
+    /// Returns the preferred type of APK, `None` meaning both.
+    pub fn apk_type(&self) -> Result<Option<ApkType>, String> {
+        self.apk_type
+            .as_deref()
+            .map(|apk_type| ApkType::from_str(apk_type, true))
+            .transpose()
+            .map_err(|e| format!("Invalid apk_type: {}", e))
+    }
+
+    /// Fills the global options not given on the command line.
+    pub fn apply_global(&self, global: &mut GlobalArgs) {
+        global.proxy = global.proxy.take().or_else(|| self.proxy.clone());
+        global.retries = global.retries.or(self.retries);
+        global.concurrency = global.concurrency.or(self.concurrency);
+        global.cache_ttl = global.cache_ttl.or(self.cache_ttl);
+    }
+
+    /// Fills the filters not given on the command line.
+    pub fn apply_filter(&self, filter: &mut FilterArgs) {
+        filter.arch = filter.arch.take().or_else(|| self.arch.clone());
+        filter.dpi = filter.dpi.take().or_else(|| self.dpi.clone());
+        // Validated when the configuration was read
+        filter.apk_type = filter.apk_type.or(self.apk_type().ok().flatten());
+    }
+
+    /// Fills the output directory if it was not given on the command line.
+    pub fn apply_output_dir(&self, output_dir: &mut Option<String>) {
+        *output_dir = output_dir.take().or_else(|| self.output_dir.clone());
+    }
+
+    /// Fills the settings of a manifest and its entries that the manifest does not set.
+    pub fn apply_manifest(&self, manifest: &mut Manifest) {
+        self.apply_output_dir(&mut manifest.output_dir);
+        for entry in &mut manifest.apps {
+            entry.arch = entry.arch.take().or_else(|| self.arch.clone());
+            entry.dpi = entry.dpi.take().or_else(|| self.dpi.clone());
+            entry.apk_type = entry.apk_type.take().or_else(|| self.apk_type.clone());
+        }
+    }
+}
+
+impl GlobalArgs {
+    /// Returns the builder of the ApkMirror client.
+    pub fn apkmirror_builder(&self) -> Result<ApkMirrorBuilder, CliError> {
+        let mut builder = ApkMirror::builder().retries(self.retries.unwrap_or_default());
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| CliError::InvalidInput(format!("Invalid proxy {}: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(cache) = self.page_cache() {
+            builder = builder.cache(cache);
+        }
+        if let (false, Some(dir)) = (self.no_session, cache_dir()) {
+            builder = builder.cookie_file(dir.join("cookies.json"));
+        }
+        builder = match self.progress_mode() {
+            ProgressMode::Bar => builder,
+            ProgressMode::Plain => builder.progress(LogReporter),
+            ProgressMode::None => builder.progress(SilentReporter),
+        };
+        Ok(builder)
+    }
+
+    /// Returns how progress is reported, `--quiet` overriding `--progress`.
+    pub fn progress_mode(&self) -> ProgressMode {
+        match self.quiet {
+            true => ProgressMode::None,
+            false => self.progress,
+        }
+    }
+
+    /// Returns the page cache, `None` if it is disabled.
+    fn page_cache(&self) -> Option<PageCache> {
+        let ttl = self.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL);
+        if self.no_cache || ttl == 0 {
+            return None;
+        }
+        let cache = PageCache::new(PageCache::default_dir()?, Duration::from_secs(ttl));
+        Some(cache.refresh(self.refresh))
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, CliError>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| CliError::InvalidInput(format!("Invalid DOWNAPK_{}: {}", key, e)))
+}
+
+/// Returns the path of the user configuration file, under `$XDG_CONFIG_HOME`
+/// or `~/.config` if it is not set.
+fn user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("downapk").join("config.toml"))
+}
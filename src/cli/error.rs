@@ -0,0 +1,90 @@
+use core::fmt;
+use downapk::errors::DownApkError;
+use reqwest::StatusCode;
+use std::process::ExitCode;
+
+/// Help text listing the exit codes, shown at the bottom of `--help`.
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    Success
+  1    Unexpected error
+  2    Invalid input or arguments
+  3    Package, release or APK file not found, or page not found (HTTP 404)
+  4    Network error, or error status returned by the website
+  5    Verification failed (challenge page served by the website, or download differs from the lockfile)
+  6    Some packages of a batch or watch run failed
+  130  Interrupted";
+
+/// Errors that end the program, each mapped to a documented exit code.
+#[derive(Debug)]
+pub enum CliError {
+    /// Invalid input given on the command line or at a prompt.
+    InvalidInput(String),
+    /// Nothing matched what the user asked for.
+    NotFound(String),
+    /// The program was interrupted with Ctrl-C.
+    Interrupted,
+    /// A download differs from what the lockfile pins.
+    Locked(String),
+    /// Some packages of a batch could not be downloaded.
+    BatchFailed { failed: usize, total: usize },
+    /// An error returned by the library.
+    DownApk(DownApkError<'static>),
+}
+
+impl CliError {
+    /// Returns the exit code the program terminates with for this error.
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            CliError::InvalidInput(_) => 2,
+            CliError::NotFound(_) => 3,
+            CliError::Locked(_) => 5,
+            CliError::BatchFailed { .. } => 6,
+            CliError::Interrupted => 130,
+            CliError::DownApk(e) => match e {
+                DownApkError::Reqwest(e) if e.status() == Some(StatusCode::NOT_FOUND) => 3,
+                DownApkError::Reqwest(_) => 4,
+                DownApkError::Challenge(_) => 5,
+                _ => 1,
+            },
+        };
+        ExitCode::from(code)
+    }
+
+    /// Saves the offending page of a challenge error into `dir`, if one was given.
+    pub fn save_challenge(&self, dir: Option<&str>) {
+        if let (CliError::DownApk(DownApkError::Challenge(page)), Some(dir)) = (self, dir) {
+            match page.save(dir) {
+                Ok(path) => eprintln!("Saved challenge page to {}", path.display()),
+                Err(e) => eprintln!("Could not save challenge page. Err: {}", e),
+            }
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::InvalidInput(e) => write!(f, "Invalid input: {}", e),
+            CliError::NotFound(e) => write!(f, "Not found: {}", e),
+            CliError::Interrupted => write!(f, "Interrupted"),
+            CliError::Locked(e) => write!(f, "Lockfile verification failed: {}", e),
+            CliError::BatchFailed { failed, total } => {
+                write!(f, "{} of {} packages failed", failed, total)
+            }
+            CliError::DownApk(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<DownApkError<'static>> for CliError {
+    fn from(e: DownApkError<'static>) -> Self {
+        CliError::DownApk(e)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        CliError::DownApk(DownApkError::from(e))
+    }
+}
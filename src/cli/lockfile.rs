@@ -0,0 +1,138 @@
+use super::error::CliError;
+use downapk::apkmirror::{ApkType, DownloadApkMirror, DownloadedFile};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Default path of the lockfile, relative to the working directory.
+pub const DEFAULT_LOCKFILE: &str = "downapk.lock";
+
+/// Records the exact files downloaded for each package, so that the same
+/// binaries can be fetched and verified again later with `--locked`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// The locked files, grouped by package in the order they were downloaded.
+    #[serde(default, rename = "file")]
+    pub files: Vec<LockedFile>,
+}
+
+/// A downloaded file pinned in the lockfile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedFile {
+    /// Android package ID the file belongs to.
+    pub package_id: String,
+    /// Version of the release.
+    pub version: String,
+    /// URL of the variant page the file is downloaded from.
+    pub variant_url: String,
+    /// Architecture of the variant.
+    pub arch: String,
+    /// Screen DPI of the variant.
+    pub screen_dpi: String,
+    /// Type of the APK.
+    pub apk_type: ApkType,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Lowercase hex encoded SHA-256 hash of the file.
+    pub sha256: String,
+}
+
+impl LockedFile {
+    /// Pins a file downloaded from the variant `item`.
+    pub fn new(package_id: &str, item: &DownloadApkMirror, file: &DownloadedFile) -> Self {
+        LockedFile {
+            package_id: package_id.to_string(),
+            version: item.version.clone(),
+            variant_url: item.variant_url.clone(),
+            arch: item.arch.clone(),
+            screen_dpi: item.screen_dpi.clone(),
+            apk_type: item.apk_type,
+            size: file.size,
+            sha256: file.sha256.clone(),
+        }
+    }
+}
+
+impl LockedFile {
+    /// Describes how the variant `item` differs from this pinned file, `None`
+    /// if its version, architecture, screen DPI and APK type all match.
+    ///
+    /// A variant page not showing its version matches any version.
+    pub fn mismatch(&self, item: &DownloadApkMirror) -> Option<String> {
+        let fields = [
+            ("version", item.version.clone(), self.version.clone()),
+            ("architecture", item.arch.clone(), self.arch.clone()),
+            (
+                "screen DPI",
+                item.screen_dpi.clone(),
+                self.screen_dpi.clone(),
+            ),
+            (
+                "APK type",
+                item.apk_type.to_string(),
+                self.apk_type.to_string(),
+            ),
+        ];
+        fields
+            .into_iter()
+            .filter(|(name, offered, _)| !(*name == "version" && offered.is_empty()))
+            .find(|(_, offered, pinned)| offered != pinned)
+            .map(|(name, offered, pinned)| {
+                format!(
+                    "{} offers {} {} instead of {}",
+                    self.variant_url, name, offered, pinned
+                )
+            })
+    }
+}
+
+impl Lockfile {
+    /// Reads the lockfile at `path`, returning an empty one if it does not exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CliError> {
+        let path = path.as_ref();
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => Err(e)?,
+        };
+        toml::from_str(&content)
+            .map_err(|e| CliError::InvalidInput(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Writes the lockfile to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CliError> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| CliError::InvalidInput(format!("Could not serialize lockfile: {}", e)))?;
+        std::fs::write(
+            path,
+            format!(
+                "# This file is generated by downapk, do not edit it by hand.\n\n{}",
+                content
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the locked files of a package.
+    pub fn package(&self, package_id: &str) -> Vec<&LockedFile> {
+        self.files
+            .iter()
+            .filter(|file| file.package_id == package_id)
+            .collect()
+    }
+
+    /// Replaces the locked files of a package.
+    pub fn update(&mut self, package_id: &str, files: Vec<LockedFile>) {
+        match self
+            .files
+            .iter()
+            .position(|file| file.package_id == package_id)
+        {
+            Some(index) => {
+                self.files.retain(|file| file.package_id != package_id);
+                let index = index.min(self.files.len());
+                self.files.splice(index..index, files);
+            }
+            None => self.files.extend(files),
+        }
+    }
+}
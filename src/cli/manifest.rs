@@ -0,0 +1,115 @@
+use super::config::DEFAULT_OUTPUT_DIR;
+use super::error::CliError;
+use super::version::VersionReq;
+use clap::ValueEnum;
+use downapk::apkmirror::ApkType;
+use serde::Deserialize;
+use std::path::Path;
+
+/// A batch manifest listing the packages to download, e.g.
+///
+/// ```toml
+/// output_dir = "downloads"
+///
+/// [[app]]
+/// package_id = "com.google.android.youtube"
+/// version = ">=19.0, <20"
+/// arch = "arm64-v8a"
+/// dpi = "nodpi"
+/// apk_type = "apk"
+/// output_dir = "downloads/youtube"
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Output directory of entries that do not set their own, `downloads` if not set.
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    /// The packages to download.
+    #[serde(default, rename = "app")]
+    pub apps: Vec<ManifestEntry>,
+}
+
+/// A package to download, with the constraints its release and variants must satisfy.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEntry {
+    /// Android package ID.
+    pub package_id: String,
+    /// Version constraint, see `VersionReq`. Defaults to `latest`.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Architecture, all if not set.
+    #[serde(default)]
+    pub arch: Option<String>,
+    /// Screen DPI, all if not set.
+    #[serde(default)]
+    pub dpi: Option<String>,
+    /// Type of APK (`apk` or `bundle`), both if not set.
+    #[serde(default)]
+    pub apk_type: Option<String>,
+    /// Output directory, the manifest's `output_dir` if not set.
+    #[serde(default)]
+    pub output_dir: Option<String>,
+}
+
+impl Manifest {
+    /// Reads and validates the manifest at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CliError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            CliError::InvalidInput(format!("Could not read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&content)
+            .map_err(|e| CliError::InvalidInput(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses and validates a manifest.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let manifest: Manifest = toml::from_str(content).map_err(|e| e.to_string())?;
+        for entry in &manifest.apps {
+            entry.version_req()?;
+            entry.apk_type()?;
+        }
+        Ok(manifest)
+    }
+
+    /// Returns the output directory of an entry.
+    pub fn output_dir<'a>(&'a self, entry: &'a ManifestEntry) -> &'a str {
+        entry
+            .output_dir
+            .as_deref()
+            .or(self.output_dir.as_deref())
+            .unwrap_or(DEFAULT_OUTPUT_DIR)
+    }
+}
+
+impl ManifestEntry {
+    /// Returns the version constraint of the entry.
+    pub fn version_req(&self) -> Result<VersionReq, String> {
+        self.version.as_deref().unwrap_or("latest").parse()
+    }
+
+    /// Returns the type of APK to filter by, `None` meaning both.
+    pub fn apk_type(&self) -> Result<Option<ApkType>, String> {
+        self.apk_type
+            .as_deref()
+            .map(|apk_type| ApkType::from_str(apk_type, true))
+            .transpose()
+            .map_err(|e| format!("Invalid apk_type of {}: {}", self.package_id, e))
+    }
+
+    /// Returns the architecture to filter by, `None` meaning all.
+    pub fn arch(&self) -> Option<&str> {
+        any_as_none(self.arch.as_deref())
+    }
+
+    /// Returns the screen DPI to filter by, `None` meaning all.
+    pub fn dpi(&self) -> Option<&str> {
+        any_as_none(self.dpi.as_deref())
+    }
+}
+
+fn any_as_none(value: Option<&str>) -> Option<&str> {
+    value.filter(|value| !value.eq_ignore_ascii_case("all"))
+}
@@ -0,0 +1,25 @@
+/// Command line arguments and subcommands
+pub mod args;
+/// Implementation of each subcommand
+pub mod commands;
+/// Layered configuration providing defaults of the command line options
+pub mod config;
+/// Errors ending the program and their exit codes
+pub mod error;
+/// Lockfile pinning downloaded files
+pub mod lockfile;
+/// Batch manifests listing packages to download
+pub mod manifest;
+/// Printing of listings and reports in table or JSON formats
+pub mod output;
+/// Reading choices from the user
+pub mod prompt;
+/// Sorting, filtering and picking of search results
+pub mod results;
+/// State of `downapk watch` remembering the last downloaded versions
+pub mod state;
+/// Comparison of versions and version constraints
+pub mod version;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,229 @@
+use clap::ValueEnum;
+use downapk::apkmirror::{AppMatch, DownloadApkMirror, DownloadedFile, ExtractedLink, ReleaseInfo};
+use serde::Serialize;
+
+/// Format used to print listings and the download report to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable numbered lines
+    Table,
+    /// A single pretty printed JSON document
+    Json,
+    /// One JSON record per line
+    Ndjson,
+}
+
+/// A record that can be printed as a numbered line in `OutputFormat::Table`.
+pub trait TableRow {
+    /// Returns the line printed after the index of the record.
+    fn row(&self) -> String;
+}
+
+impl TableRow for ExtractedLink {
+    fn row(&self) -> String {
+        format!("{} {} {}", self.title, self.uploaded, self.file_size)
+    }
+}
+
+impl TableRow for AppMatch {
+    fn row(&self) -> String {
+        format!(
+            "{} ({}) {} by {}",
+            self.name,
+            self.package_id.as_deref().unwrap_or("unknown package"),
+            self.latest_version,
+            self.developer
+        )
+    }
+}
+
+impl TableRow for DownloadApkMirror {
+    fn row(&self) -> String {
+        format!(
+            "{} {} {} {} {}",
+            self.version, self.apk_type, self.arch, self.screen_dpi, self.min_version
+        )
+    }
+}
+
+impl TableRow for DownloadedFile {
+    fn row(&self) -> String {
+        format!(
+            "{} ({} bytes, sha256 {})",
+            self.path.display(),
+            self.size,
+            self.sha256
+        )
+    }
+}
+
+/// A record that can be printed as `Name: value` lines in `OutputFormat::Table`.
+pub trait Details {
+    /// Returns the name and value of each field shown.
+    fn details(&self) -> Vec<(&'static str, String)>;
+}
+
+impl Details for ReleaseInfo {
+    fn details(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Title", self.title.clone()),
+            ("Version", self.version.clone()),
+            (
+                "Package",
+                self.package_id.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            ("Developer", self.developer.clone()),
+            (
+                "Category",
+                self.category.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            ("Uploaded", self.uploaded.clone()),
+            (
+                "Signature",
+                self.signature.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            ("Permissions", self.permissions.join(", ")),
+            ("Description", self.description.clone()),
+            ("What's new", self.whats_new.clone()),
+            ("Link", self.url.clone()),
+        ]
+    }
+}
+
+/// Report of the files downloaded for a package, printed once all downloads finish.
+#[derive(Debug, Serialize)]
+pub struct DownloadReport<'a> {
+    /// The package the files belong to.
+    pub package_id: &'a str,
+    /// The downloaded files.
+    pub files: &'a [DownloadedFile],
+}
+
+/// Prints a listing of records to stdout in the given format.
+pub fn print_list<T: Serialize + TableRow>(format: OutputFormat, items: &[T]) {
+    match format {
+        OutputFormat::Table => {
+            for (i, item) in items.iter().enumerate() {
+                println!("{}. {}", i + 1, item.row());
+            }
+        }
+        OutputFormat::Json => print_json(&items),
+        OutputFormat::Ndjson => {
+            for item in items {
+                print_json_line(item);
+            }
+        }
+    }
+}
+
+/// Prints the details of a single record to stdout in the given format.
+pub fn print_details<T: Serialize + Details>(format: OutputFormat, item: &T) {
+    match format {
+        OutputFormat::Table => {
+            let details = item.details();
+            let width = details
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or_default();
+            for (name, value) in details {
+                println!(
+                    "{:width$}  {}",
+                    format!("{}:", name),
+                    value,
+                    width = width + 1
+                );
+            }
+        }
+        OutputFormat::Json => print_json(item),
+        OutputFormat::Ndjson => print_json_line(item),
+    }
+}
+
+/// Prints the report of downloaded files to stdout in the given format.
+pub fn print_report(format: OutputFormat, report: &DownloadReport) {
+    match format {
+        OutputFormat::Table => {
+            println!("Downloaded successfully");
+            for file in report.files {
+                println!("{}", file.row());
+            }
+        }
+        OutputFormat::Json => print_json(report),
+        OutputFormat::Ndjson => print_json_line(report),
+    }
+}
+
+/// Changes of the releases of a package, printed by `downapk changelog`.
+#[derive(Debug, Serialize)]
+pub struct Changelog<'a> {
+    /// The package the releases belong to.
+    pub package_id: &'a str,
+    /// The releases, newest first.
+    pub releases: Vec<ChangelogEntry>,
+}
+
+/// Changes of one release, from the "What's new" section of its release page.
+#[derive(Debug, Serialize)]
+pub struct ChangelogEntry {
+    /// The version of the release.
+    pub version: String,
+    /// The title of the release.
+    pub title: String,
+    /// The date when the release was uploaded, as shown on the website.
+    pub uploaded: String,
+    /// The URL of the release page.
+    pub link: String,
+    /// The changes of the release, one per line.
+    pub whats_new: String,
+}
+
+impl Changelog<'_> {
+    /// Returns the changelog as a markdown document, with a section per release.
+    pub fn markdown(&self) -> String {
+        let mut markdown = format!("# {} changelog\n", self.package_id);
+        for release in &self.releases {
+            markdown.push_str(&format!("\n## {}", release.version));
+            if !release.uploaded.is_empty() {
+                markdown.push_str(&format!(" ({})", release.uploaded));
+            }
+            markdown.push_str("\n\n");
+            match release.whats_new.as_str() {
+                "" => markdown.push_str("No changes listed.\n"),
+                whats_new => {
+                    for line in whats_new.lines() {
+                        markdown.push_str(&format!("- {}\n", line));
+                    }
+                }
+            }
+        }
+        markdown
+    }
+}
+
+/// Prints a changelog to stdout, as markdown in `OutputFormat::Table`.
+pub fn print_changelog(format: OutputFormat, changelog: &Changelog) {
+    match format {
+        OutputFormat::Table => print!("{}", changelog.markdown()),
+        OutputFormat::Json => print_json(changelog),
+        OutputFormat::Ndjson => {
+            for release in &changelog.releases {
+                print_json_line(release);
+            }
+        }
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Could not serialize output. Err: {}", e),
+    }
+}
+
+fn print_json_line<T: Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Could not serialize output. Err: {}", e),
+    }
+}
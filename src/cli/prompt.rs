@@ -0,0 +1,26 @@
+use super::error::CliError;
+
+/// Prompts on stderr, keeping stdout for listings and reports.
+pub fn read_input(msg: &str) -> Result<usize, CliError> {
+    eprintln!("{}", msg);
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        return Err(CliError::InvalidInput(
+            "No input given, stdin was closed".to_string(),
+        ));
+    }
+    input.trim().parse().map_err(|err| {
+        CliError::InvalidInput(format!("{:?} is not a number: {}", input.trim(), err))
+    })
+}
+
+/// Checks that a 1-based `choice` is within `1..=len` and returns it as a 0-based index.
+pub fn check_index(choice: usize, len: usize, what: &str) -> Result<usize, CliError> {
+    if choice == 0 || choice > len {
+        return Err(CliError::InvalidInput(format!(
+            "Invalid {}. Choose a number from 1 to {}",
+            what, len
+        )));
+    }
+    Ok(choice - 1)
+}
@@ -0,0 +1,91 @@
+use super::args::{PickPolicy, ResultArgs, SortKey};
+use super::version::compare_versions;
+use downapk::apkmirror::metadata::parse_file_size;
+use downapk::apkmirror::ExtractedLink;
+use std::cmp::Ordering;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime, Time};
+
+impl ResultArgs {
+    /// Filters and sorts `results`.
+    ///
+    /// Results whose upload date or size could not be parsed are dropped by
+    /// `--since` and `--max-size` respectively, and listed last when sorting.
+    pub fn apply(&self, mut results: Vec<ExtractedLink>) -> Vec<ExtractedLink> {
+        let title_contains = self.title_contains.as_deref().map(str::to_lowercase);
+        results.retain(|result| {
+            self.since
+                .is_none_or(|since| result.uploaded_at.is_some_and(|at| at >= since))
+                && self
+                    .max_size
+                    .is_none_or(|max| result.file_size_bytes.is_some_and(|size| size <= max))
+                && title_contains
+                    .as_deref()
+                    .is_none_or(|text| result.title.to_lowercase().contains(text))
+        });
+        if let Some(key) = self.sort {
+            // Stable, so that equal results keep the order of the website
+            results.sort_by(|a, b| key.compare(b, a));
+        }
+        results
+    }
+}
+
+impl SortKey {
+    /// Compares two results in ascending order of the key, unknown values first.
+    fn compare(&self, a: &ExtractedLink, b: &ExtractedLink) -> Ordering {
+        match self {
+            SortKey::Date => a.uploaded_at.cmp(&b.uploaded_at),
+            SortKey::Version => compare_versions(&a.version, &b.version),
+            SortKey::Size => a.file_size_bytes.cmp(&b.file_size_bytes),
+            SortKey::Downloads => a.download_count.cmp(&b.download_count),
+        }
+    }
+}
+
+impl PickPolicy {
+    /// Returns the 0-based index of the picked result, the first one listed
+    /// if several are equally new or large. `results` must not be empty.
+    pub fn pick(&self, results: &[ExtractedLink]) -> usize {
+        let key = match self {
+            PickPolicy::First => return 0,
+            PickPolicy::Newest => SortKey::Date,
+            PickPolicy::Largest => SortKey::Size,
+        };
+        (0..results.len())
+            .reduce(
+                |picked, i| match key.compare(&results[i], &results[picked]) {
+                    Ordering::Greater => i,
+                    _ => picked,
+                },
+            )
+            .unwrap_or_default()
+    }
+}
+
+/// Parses the date of `--since`, either `2024-01-05` meaning midnight UTC or
+/// an RFC 3339 date and time such as `2024-01-05T15:00:00Z`.
+pub fn parse_date(date: &str) -> Result<OffsetDateTime, String> {
+    if let Ok(date_time) = OffsetDateTime::parse(date, &Rfc3339) {
+        return Ok(date_time);
+    }
+    Date::parse(date, format_description!("[year]-[month]-[day]"))
+        .map(|date| date.with_time(Time::MIDNIGHT).assume_utc())
+        .map_err(|_| format!("{:?} is not a date like 2024-01-05", date))
+}
+
+/// Parses the size of `--max-size`, either a number of bytes or a number
+/// followed by a unit such as `100MB` or `1.5 GB`.
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    if let Ok(bytes) = size.parse() {
+        return Ok(bytes);
+    }
+    let (number, unit) = size.split_at(
+        size.find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(size.len()),
+    );
+    parse_file_size(&format!("{} {}", number.trim(), unit))
+        .ok_or_else(|| format!("{:?} is not a size like 100MB", size))
+}
@@ -0,0 +1,84 @@
+use super::error::CliError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default path of the watch state file, relative to the working directory.
+pub const DEFAULT_STATE_FILE: &str = "downapk-state.toml";
+
+/// Remembers the last version downloaded by `downapk watch` for each package.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WatchState {
+    #[serde(default)]
+    pub packages: BTreeMap<String, PackageState>,
+    /// URL of the app page of each package, resolved once so that later runs
+    /// only read the feed of the app.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub apps: BTreeMap<String, String>,
+}
+
+/// The last version downloaded of a package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageState {
+    /// Version of the last downloaded release.
+    pub version: String,
+    /// When the release was downloaded, in seconds since the Unix epoch.
+    pub downloaded_at: u64,
+}
+
+impl WatchState {
+    /// Reads the state file at `path`, returning an empty state if it does not exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CliError> {
+        let path = path.as_ref();
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => Err(e)?,
+        };
+        toml::from_str(&content)
+            .map_err(|e| CliError::InvalidInput(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Writes the state file to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CliError> {
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            CliError::InvalidInput(format!("Could not serialize watch state: {}", e))
+        })?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Returns the last downloaded version of a package.
+    pub fn version(&self, package_id: &str) -> Option<&str> {
+        self.packages
+            .get(package_id)
+            .map(|state| state.version.as_str())
+    }
+
+    /// Records that `version` of a package was just downloaded.
+    pub fn record(&mut self, package_id: &str, version: &str) {
+        let downloaded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.packages.insert(
+            package_id.to_string(),
+            PackageState {
+                version: version.to_string(),
+                downloaded_at,
+            },
+        );
+    }
+
+    /// Returns the URL of the app page of a package, if it was resolved before.
+    pub fn app_url(&self, package_id: &str) -> Option<&str> {
+        self.apps.get(package_id).map(String::as_str)
+    }
+
+    /// Records the URL of the app page a package was resolved to.
+    pub fn record_app(&mut self, package_id: &str, app_url: &str) {
+        self.apps
+            .insert(package_id.to_string(), app_url.to_string());
+    }
+}
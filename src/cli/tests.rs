@@ -0,0 +1,478 @@
+use super::args::{Cli, Command, PickPolicy, ProgressMode, ResultArgs, SortKey};
+use super::commands::changelog::in_range;
+use super::commands::watch::{new_versions, WatchReport, WatchStatus};
+use super::config::Config;
+use super::error::{CliError, EXIT_CODES_HELP};
+use super::lockfile::{LockedFile, Lockfile};
+use super::manifest::Manifest;
+use super::output::{Changelog, ChangelogEntry, TableRow};
+use super::results::{parse_date, parse_size};
+use super::state::WatchState;
+use super::version::{compare_versions, VersionReq};
+use clap::Parser;
+use downapk::apkmirror::metadata::parse_uploaded;
+use downapk::apkmirror::{ApkType, DownloadApkMirror, ExtractedLink};
+use downapk::errors::{ChallengePage, DownApkError};
+use std::cmp::Ordering;
+use std::process::ExitCode;
+
+#[test]
+fn test_compare_versions() {
+    assert_eq!(compare_versions("19.02.34", "19.2.34"), Ordering::Equal);
+    assert_eq!(compare_versions("19.10.1", "19.9.5"), Ordering::Greater);
+    assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+    assert_eq!(compare_versions("1.0-beta", "1.0"), Ordering::Less);
+    assert_eq!(
+        compare_versions("390.0.0.9.116", "389.1.0.1.100"),
+        Ordering::Greater
+    );
+}
+
+#[test]
+fn test_version_req() {
+    let latest: VersionReq = "latest".parse().unwrap();
+    assert!(latest.matches("1.0"));
+
+    let exact: VersionReq = "=19.02.34".parse().unwrap();
+    assert!(exact.matches("19.2.34"));
+    assert!(!exact.matches("19.02.35"));
+
+    let prefix: VersionReq = "19.02.*".parse().unwrap();
+    assert!(prefix.matches("19.02.34"));
+    assert!(!prefix.matches("19.03.1"));
+
+    let range: VersionReq = ">=19.0, <20".parse().unwrap();
+    assert!(range.matches("19.45.2"));
+    assert!(!range.matches("20.0.1"));
+    assert!(!range.matches("18.9"));
+    assert_eq!(range.to_string(), ">=19.0, <20");
+
+    assert!(">=".parse::<VersionReq>().is_err());
+}
+
+#[test]
+fn test_parse_manifest() {
+    let manifest = Manifest::parse(
+        r#"
+        output_dir = "apks"
+
+        [[app]]
+        package_id = "com.google.android.youtube"
+        version = ">=19.0"
+        arch = "arm64-v8a"
+        dpi = "all"
+        apk_type = "apk"
+
+        [[app]]
+        package_id = "com.instagram.lite"
+        output_dir = "apks/instagram"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(manifest.output_dir.as_deref(), Some("apks"));
+    assert_eq!(manifest.apps.len(), 2);
+    assert_eq!(manifest.apps[0].arch(), Some("arm64-v8a"));
+    assert_eq!(manifest.apps[0].dpi(), None);
+    assert!(manifest.apps[0].apk_type().unwrap().is_some());
+    assert_eq!(manifest.apps[1].version_req().unwrap(), VersionReq::Latest);
+
+    assert!(Manifest::parse("[[app]]\npackage_id = \"a\"\napk_type = \"zip\"").is_err());
+    assert!(Manifest::parse("[[app]]\npackage = \"a\"").is_err());
+}
+
+fn locked_file(package_id: &str, version: &str) -> LockedFile {
+    LockedFile {
+        package_id: package_id.to_string(),
+        version: version.to_string(),
+        variant_url: format!(
+            "https://www.apkmirror.com/apk/x/{}/{}/",
+            package_id, version
+        ),
+        arch: "arm64-v8a".to_string(),
+        screen_dpi: "nodpi".to_string(),
+        apk_type: ApkType::Apk,
+        size: 1024,
+        sha256: "ab".repeat(32),
+    }
+}
+
+#[test]
+fn test_lockfile_update() {
+    let mut lockfile = Lockfile::default();
+    lockfile.update("a", vec![locked_file("a", "1.0")]);
+    lockfile.update("b", vec![locked_file("b", "2.0")]);
+    lockfile.update("a", vec![locked_file("a", "1.1"), locked_file("a", "1.1")]);
+
+    let packages: Vec<_> = lockfile
+        .files
+        .iter()
+        .map(|file| (file.package_id.as_str(), file.version.as_str()))
+        .collect();
+    assert_eq!(packages, vec![("a", "1.1"), ("a", "1.1"), ("b", "2.0")]);
+    assert_eq!(lockfile.package("b"), vec![&locked_file("b", "2.0")]);
+
+    let content = toml::to_string_pretty(&lockfile).unwrap();
+    assert!(content.contains("apk_type = \"APK\""));
+    let parsed: Lockfile = toml::from_str(&content).unwrap();
+    assert_eq!(parsed.files, lockfile.files);
+
+    let locked = locked_file("a", "1.1");
+    let mut item = DownloadApkMirror {
+        version: "1.1".to_string(),
+        download_link: String::new(),
+        variant_url: locked.variant_url.clone(),
+        apk_type: ApkType::Apk,
+        arch: "arm64-v8a".to_string(),
+        min_version: String::new(),
+        screen_dpi: "nodpi".to_string(),
+    };
+    assert_eq!(locked.mismatch(&item), None);
+    item.version.clear();
+    assert_eq!(locked.mismatch(&item), None);
+    item.screen_dpi = "120-640dpi".to_string();
+    assert_eq!(
+        locked.mismatch(&item),
+        Some(format!(
+            "{} offers screen DPI 120-640dpi instead of nodpi",
+            locked.variant_url
+        ))
+    );
+    item.screen_dpi = "nodpi".to_string();
+    item.apk_type = ApkType::Bundle;
+    assert!(locked.mismatch(&item).unwrap().contains("APK type"));
+}
+
+#[test]
+fn test_new_versions() {
+    let versions = [
+        "19.05.1", "19.04.2", "19.4.2", "19.03.0", "18.49.1", "20.01.0",
+    ];
+    let range: VersionReq = "<20".parse().unwrap();
+
+    assert_eq!(
+        new_versions(versions, &range, Some("19.03.0")),
+        vec!["19.04.2", "19.05.1"]
+    );
+    assert_eq!(
+        new_versions(versions, &range, Some("19.05.1")),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        new_versions(versions, &VersionReq::Latest, None),
+        vec!["18.49.1", "19.03.0", "19.04.2", "19.05.1", "20.01.0"]
+    );
+}
+
+#[test]
+fn test_watch_report_row() {
+    let change = |version: &str, whats_new: &str| ChangelogEntry {
+        version: version.to_string(),
+        title: format!("App {}", version),
+        uploaded: String::new(),
+        link: String::new(),
+        whats_new: whats_new.to_string(),
+    };
+    let report = WatchReport {
+        package_id: "com.example".to_string(),
+        status: WatchStatus::Available,
+        previous: Some("1.0".to_string()),
+        latest: Some("1.2".to_string()),
+        new_versions: vec!["1.1".to_string(), "1.2".to_string()],
+        changes: vec![
+            change("1.1", ""),
+            change("1.2", "Bug fixes\nFaster startup"),
+        ],
+        files: vec![],
+        error: None,
+    };
+    assert_eq!(
+        report.row(),
+        "[available] com.example 1.0 -> 1.2 (new: 1.1, 1.2)\n  1.2:\n    Bug fixes\n    Faster startup"
+    );
+}
+
+#[test]
+fn test_watch_state() {
+    // State files written before app pages were remembered still load
+    let mut state: WatchState = toml::from_str(
+        r#"
+        [packages."com.google.android.youtube"]
+        version = "19.02.34"
+        downloaded_at = 1704467520
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        state.version("com.google.android.youtube"),
+        Some("19.02.34")
+    );
+    assert_eq!(state.app_url("com.google.android.youtube"), None);
+
+    state.record_app(
+        "com.google.android.youtube",
+        "https://www.apkmirror.com/apk/google-inc/youtube/",
+    );
+    let parsed: WatchState = toml::from_str(&toml::to_string_pretty(&state).unwrap()).unwrap();
+    assert_eq!(
+        parsed.app_url("com.google.android.youtube"),
+        Some("https://www.apkmirror.com/apk/google-inc/youtube/")
+    );
+    assert_eq!(parsed.packages, state.packages);
+}
+
+#[test]
+fn test_config_layers() {
+    let user = Config::parse(
+        r#"
+        output_dir = "apks"
+        arch = "arm64-v8a"
+        retries = 2
+        "#,
+    )
+    .unwrap();
+    let project = Config::parse(
+        r#"
+        arch = "x86_64"
+        apk_type = "bundle"
+        "#,
+    )
+    .unwrap();
+    let env = Config::from_env([
+        ("DOWNAPK_RETRIES".to_string(), "5".to_string()),
+        ("DOWNAPK_CONCURRENCY".to_string(), "4".to_string()),
+        ("PATH".to_string(), "/usr/bin".to_string()),
+    ])
+    .unwrap();
+
+    let config = user.merge(project).merge(env);
+    assert_eq!(config.output_dir.as_deref(), Some("apks"));
+    assert_eq!(config.arch.as_deref(), Some("x86_64"));
+    assert_eq!(config.apk_type(), Ok(Some(ApkType::Bundle)));
+    assert_eq!(config.retries, Some(5));
+    assert_eq!(config.concurrency, Some(4));
+    assert_eq!(config.proxy, None);
+
+    assert!(Config::parse("unknown = 1").is_err());
+    assert!(Config::parse(r#"apk_type = "zip""#).is_err());
+    assert!(Config::from_env([("DOWNAPK_RETRIES".to_string(), "many".to_string())]).is_err());
+}
+
+#[test]
+fn test_progress_mode() {
+    let cli = Cli::parse_from(["downapk", "search", "-p", "com.example"]);
+    assert_eq!(cli.global.progress_mode(), ProgressMode::Bar);
+
+    let cli = Cli::parse_from([
+        "downapk",
+        "search",
+        "-p",
+        "com.example",
+        "--progress",
+        "plain",
+    ]);
+    assert_eq!(cli.global.progress_mode(), ProgressMode::Plain);
+
+    let cli = Cli::parse_from(["downapk", "--progress", "plain", "-q", "search", "-p", "x"]);
+    assert_eq!(cli.global.progress_mode(), ProgressMode::None);
+}
+
+fn release(title: &str, version: &str, uploaded: &str, size: Option<u64>) -> ExtractedLink {
+    ExtractedLink {
+        title: title.to_string(),
+        version: version.to_string(),
+        uploaded_at: parse_uploaded(uploaded),
+        file_size_bytes: size,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_filter_results() {
+    let results = vec![
+        release(
+            "App 1.9 beta",
+            "1.9",
+            "March 1, 2024 at 9:00AM UTC",
+            Some(50),
+        ),
+        release(
+            "App 1.10",
+            "1.10",
+            "February 1, 2024 at 9:00AM UTC",
+            Some(80),
+        ),
+        release("App 1.8", "1.8", "unknown", None),
+        release("App 1.7", "1.7", "January 1, 2024 at 9:00AM UTC", Some(120)),
+    ];
+    let titles = |results: &[ExtractedLink]| {
+        results
+            .iter()
+            .map(|result| result.title.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let options = ResultArgs {
+        sort: Some(SortKey::Version),
+        ..Default::default()
+    };
+    assert_eq!(
+        titles(&options.apply(results.clone())),
+        ["App 1.10", "App 1.9 beta", "App 1.8", "App 1.7"]
+    );
+
+    // Unknown dates are listed last
+    let options = ResultArgs {
+        sort: Some(SortKey::Date),
+        ..Default::default()
+    };
+    assert_eq!(
+        titles(&options.apply(results.clone())),
+        ["App 1.9 beta", "App 1.10", "App 1.7", "App 1.8"]
+    );
+
+    let options = ResultArgs {
+        since: Some(parse_date("2024-02-01").unwrap()),
+        max_size: Some(parse_size("100").unwrap()),
+        title_contains: Some("app 1.1".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(titles(&options.apply(results.clone())), ["App 1.10"]);
+
+    assert_eq!(PickPolicy::First.pick(&results), 0);
+    assert_eq!(PickPolicy::Newest.pick(&results), 0);
+    assert_eq!(PickPolicy::Largest.pick(&results), 3);
+
+    assert_eq!(parse_size("100MB"), Ok(104_857_600));
+    assert_eq!(parse_size("1.5 GB"), Ok(1_610_612_736));
+    assert!(parse_size("lots").is_err());
+    assert_eq!(
+        parse_date("2024-01-05T15:00:00Z").map(|date| date.unix_timestamp()),
+        Ok(1_704_466_800)
+    );
+    assert!(parse_date("05/01/2024").is_err());
+
+    let cli = Cli::parse_from([
+        "downapk",
+        "-p",
+        "com.example",
+        "--pick",
+        "newest",
+        "--sort",
+        "size",
+    ]);
+    assert_eq!(cli.download.pick, Some(PickPolicy::Newest));
+    assert_eq!(cli.download.results.sort, Some(SortKey::Size));
+}
+
+#[test]
+fn test_changelog_markdown() {
+    let entry = |version: &str, uploaded: &str, whats_new: &str| ChangelogEntry {
+        version: version.to_string(),
+        title: format!("App {}", version),
+        uploaded: uploaded.to_string(),
+        link: String::new(),
+        whats_new: whats_new.to_string(),
+    };
+    let changelog = Changelog {
+        package_id: "com.example",
+        releases: vec![
+            entry("1.2", "March 1, 2024", "Bug fixes\nFaster startup"),
+            entry("1.1", "", ""),
+        ],
+    };
+    assert_eq!(
+        changelog.markdown(),
+        "# com.example changelog\n\
+        \n## 1.2 (March 1, 2024)\n\n- Bug fixes\n- Faster startup\n\
+        \n## 1.1\n\nNo changes listed.\n"
+    );
+
+    let cli = Cli::parse_from(["downapk", "changelog", "-p", "com.example", "--from", "1.1"]);
+    match cli.command {
+        Some(Command::Changelog(args)) => {
+            assert_eq!(args.from, "1.1");
+            assert_eq!(args.to, None);
+            assert_eq!(args.max_pages, 10);
+        }
+        command => panic!("unexpected command {:?}", command),
+    }
+    assert!(Cli::try_parse_from(["downapk", "changelog", "-p", "com.example"]).is_err());
+    assert!(Cli::try_parse_from([
+        "downapk",
+        "changelog",
+        "-p",
+        "com.example",
+        "--from",
+        "1.1",
+        "--max-pages",
+        "0"
+    ])
+    .is_err());
+
+    // Releases of an older track uploaded among newer ones are left out without ending the changelog
+    let uploads = ["2.1", "1.9.8", "2.0", "1.0", "1.5"];
+    let kept: Vec<_> = uploads
+        .into_iter()
+        .filter(|version| in_range(version, "1.5", Some("2.0")))
+        .collect();
+    assert_eq!(kept, ["1.9.8", "2.0", "1.5"]);
+    assert!(in_range("3.0", "1.5", None));
+}
+
+#[tokio::test]
+async fn test_exit_codes() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Answers every request with a 404, then stops listening for the network error
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1024];
+        let _ = socket.read(&mut buf).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+    });
+    let not_found = reqwest::get(&url)
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap_err();
+    server.await.unwrap();
+    let network = reqwest::get(&url).await.unwrap_err();
+
+    let challenge = DownApkError::Challenge(ChallengePage {
+        url,
+        status: 403,
+        reason: "Cloudflare browser check".to_string(),
+        html: String::new(),
+    });
+    let cases = [
+        (CliError::DownApk("unexpected".into()), 1),
+        (CliError::InvalidInput("bad".to_string()), 2),
+        (CliError::NotFound("missing".to_string()), 3),
+        (CliError::DownApk(not_found.into()), 3),
+        (CliError::DownApk(network.into()), 4),
+        (CliError::DownApk(challenge), 5),
+        (CliError::Locked("differs".to_string()), 5),
+        (
+            CliError::BatchFailed {
+                failed: 1,
+                total: 2,
+            },
+            6,
+        ),
+        (CliError::Interrupted, 130),
+    ];
+    for (error, code) in cases {
+        assert_eq!(error.exit_code(), ExitCode::from(code), "{}", error);
+        assert!(
+            EXIT_CODES_HELP.contains(&format!("\n  {} ", code)),
+            "exit code {} is not documented",
+            code
+        );
+    }
+}
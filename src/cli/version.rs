@@ -0,0 +1,146 @@
+use core::fmt;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// A part of a version string, either numeric or textual.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Part<'a> {
+    // Text sorts before numbers, so that `1.0-beta` is older than `1.0`
+    Text(&'a str),
+    Num(u64),
+}
+
+fn parts(version: &str) -> Vec<Part<'_>> {
+    version
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse() {
+            Ok(num) => Part::Num(num),
+            Err(_) => Part::Text(part),
+        })
+        .collect()
+}
+
+/// Compares two version strings such as `19.02.34` part by part, numerically
+/// where both parts are numbers. Missing parts count as `0`.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (parts(a), parts(b));
+    for i in 0..a.len().max(b.len()) {
+        let a = a.get(i).unwrap_or(&Part::Num(0));
+        let b = b.get(i).unwrap_or(&Part::Num(0));
+        match a.cmp(b) {
+            Ordering::Equal => continue,
+            ordering => return ordering,
+        }
+    }
+    Ordering::Equal
+}
+
+/// A comparison operator of a `VersionReq::Compare` constraint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+/// A constraint on the version of a release.
+///
+/// Parsed from `latest`, an exact version (`1.2.3` or `=1.2.3`), a prefix
+/// (`1.2.*`) or comma separated comparisons (`>=1.2, <2`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionReq {
+    Latest,
+    Exact(String),
+    Prefix(String),
+    Compare(Vec<(Op, String)>),
+}
+
+impl VersionReq {
+    /// Checks whether `version` satisfies the constraint.
+    pub fn matches(&self, version: &str) -> bool {
+        match self {
+            VersionReq::Latest => true,
+            VersionReq::Exact(exact) => compare_versions(version, exact) == Ordering::Equal,
+            VersionReq::Prefix(prefix) => {
+                let (version, prefix) = (parts(version), parts(prefix));
+                version.len() >= prefix.len() && version[..prefix.len()] == prefix[..]
+            }
+            VersionReq::Compare(comparisons) => comparisons.iter().all(|(op, bound)| {
+                let ordering = compare_versions(version, bound);
+                match op {
+                    Op::Greater => ordering == Ordering::Greater,
+                    Op::GreaterEq => ordering != Ordering::Less,
+                    Op::Less => ordering == Ordering::Less,
+                    Op::LessEq => ordering != Ordering::Greater,
+                }
+            }),
+        }
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() || s.eq_ignore_ascii_case("latest") || s == "*" {
+            return Ok(VersionReq::Latest);
+        }
+        if let Some(prefix) = s.strip_suffix(".*") {
+            return Ok(VersionReq::Prefix(prefix.to_string()));
+        }
+        if !s.starts_with(['>', '<']) {
+            return Ok(VersionReq::Exact(
+                s.trim_start_matches('=').trim().to_string(),
+            ));
+        }
+
+        let mut comparisons = vec![];
+        for comparison in s.split(',').map(str::trim) {
+            let (op, bound) = if let Some(bound) = comparison.strip_prefix(">=") {
+                (Op::GreaterEq, bound)
+            } else if let Some(bound) = comparison.strip_prefix("<=") {
+                (Op::LessEq, bound)
+            } else if let Some(bound) = comparison.strip_prefix('>') {
+                (Op::Greater, bound)
+            } else if let Some(bound) = comparison.strip_prefix('<') {
+                (Op::Less, bound)
+            } else {
+                return Err(format!("Invalid version constraint {:?}", comparison));
+            };
+            let bound = bound.trim();
+            if bound.is_empty() {
+                return Err(format!("Missing version in constraint {:?}", comparison));
+            }
+            comparisons.push((op, bound.to_string()));
+        }
+        Ok(VersionReq::Compare(comparisons))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionReq::Latest => write!(f, "latest"),
+            VersionReq::Exact(version) => write!(f, "{}", version),
+            VersionReq::Prefix(prefix) => write!(f, "{}.*", prefix),
+            VersionReq::Compare(comparisons) => {
+                let comparisons: Vec<String> = comparisons
+                    .iter()
+                    .map(|(op, bound)| {
+                        let op = match op {
+                            Op::Greater => ">",
+                            Op::GreaterEq => ">=",
+                            Op::Less => "<",
+                            Op::LessEq => "<=",
+                        };
+                        format!("{}{}", op, bound)
+                    })
+                    .collect();
+                write!(f, "{}", comparisons.join(", "))
+            }
+        }
+    }
+}
@@ -1,17 +1,69 @@
 use core::fmt;
+#[cfg(feature = "progress")]
 use indicatif::style::TemplateError;
 use reqwest::Error as ReqwestError;
 use scraper::error::SelectorErrorKind as ScraperSelectorErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug)]
 pub enum DownApkError<'a> {
     Reqwest(ReqwestError),
     Selector(ScraperSelectorErrorKind<'a>),
-    Template(TemplateError),
+    /// The message of an invalid progress bar template, kept without the
+    /// `progress` feature so that enabling it does not change the enum.
+    Template(String),
     IoError(std::io::Error),
+    Challenge(ChallengePage),
     Other(String),
 }
 
+/// An anti-bot challenge or interstitial page served instead of the requested page.
+///
+/// Keeps the offending HTML around so it can be saved to disk for diagnosis.
+pub struct ChallengePage {
+    /// The URL that was requested.
+    pub url: String,
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// A short description of the kind of challenge that was detected.
+    pub reason: String,
+    /// The body of the response.
+    pub html: String,
+}
+
+impl ChallengePage {
+    /// Writes the offending HTML into `dir`, creating it if needed.
+    ///
+    /// # Returns
+    ///
+    /// The path of the written file or an `Error` if it could not be written.
+    pub fn save(&self, dir: impl AsRef<Path>) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(&dir)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let path = dir
+            .as_ref()
+            .join(format!("challenge-{}-{}.html", self.status, timestamp));
+        std::fs::write(&path, &self.html)?;
+        Ok(path)
+    }
+}
+
+/// The HTML body is left out as it is usually several kilobytes long.
+impl fmt::Debug for ChallengePage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChallengePage")
+            .field("url", &self.url)
+            .field("status", &self.status)
+            .field("reason", &self.reason)
+            .field("html_len", &self.html.len())
+            .finish()
+    }
+}
+
 impl fmt::Display for DownApkError<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -19,6 +71,11 @@ impl fmt::Display for DownApkError<'_> {
             DownApkError::Selector(e) => write!(f, "Selector error: {}", e),
             DownApkError::Template(e) => write!(f, "Template error: {}", e),
             DownApkError::IoError(e) => write!(f, "IO error: {}", e),
+            DownApkError::Challenge(page) => write!(
+                f,
+                "Challenge page served for {} (HTTP {}): {}",
+                page.url, page.status, page.reason
+            ),
             DownApkError::Other(e) => write!(f, "Other error: {}", e),
         }
     }
@@ -36,9 +93,10 @@ impl<'a> From<ScraperSelectorErrorKind<'a>> for DownApkError<'a> {
     }
 }
 
+#[cfg(feature = "progress")]
 impl From<TemplateError> for DownApkError<'_> {
     fn from(e: TemplateError) -> Self {
-        DownApkError::Template(e)
+        DownApkError::Template(e.to_string())
     }
 }
 
@@ -65,9 +123,8 @@ impl std::error::Error for DownApkError<'static> {
         match self {
             DownApkError::Reqwest(e) => Some(e),
             DownApkError::Selector(e) => Some(e),
-            DownApkError::Template(e) => Some(e),
             DownApkError::IoError(e) => Some(e),
-            DownApkError::Other(_) => None,
+            DownApkError::Template(_) | DownApkError::Challenge(_) | DownApkError::Other(_) => None,
         }
     }
 }
@@ -2,6 +2,22 @@ use crate::errors::DownApkError;
 use scraper::Selector;
 
 /// Returns a `Selector` from a given `&str`
-pub fn selector(selector: &str) -> Result<Selector, DownApkError> {
+pub fn selector(selector: &str) -> Result<Selector, DownApkError<'_>> {
     Selector::parse(selector).map_err(|e| e.into())
 }
+
+/// Encodes `value` for the query string of a URL, like a submitted HTML form:
+/// spaces become `+` and bytes other than letters, digits and `*-._` are
+/// percent-encoded, so that e.g. `&` or `#` in a search query stay part of it.
+pub fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b' ' => "+".to_string(),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+//! Reqwest reads the proxy environment variables once per process, so this
+//! test runs in its own test binary instead of with the unit tests.
+
+use downapk::apkmirror::progress::SilentReporter;
+use downapk::apkmirror::ApkMirror;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Stands in for a proxy, answering the first request with a homepage with
+/// the search form and returning the request line it received.
+async fn proxy() -> (String, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let len = socket.read(&mut buf).await.unwrap();
+            assert!(len > 0, "Connection closed before the end of the request");
+            request.extend_from_slice(&buf[..len]);
+        }
+        let body = "<html><button class='searchButton'></button></html>";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        // A CONNECT tunnel is refused by closing the connection instead
+        if !request.starts_with(b"CONNECT") {
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+        let request = String::from_utf8_lossy(&request);
+        request.lines().next().unwrap_or_default().to_string()
+    });
+    (url, server)
+}
+
+#[tokio::test]
+async fn test_env_proxy() {
+    let (all_proxy, all_server) = proxy().await;
+    let (https_proxy, https_server) = proxy().await;
+    for var in [
+        "HTTP_PROXY",
+        "http_proxy",
+        "https_proxy",
+        "all_proxy",
+        "NO_PROXY",
+        "no_proxy",
+    ] {
+        std::env::remove_var(var);
+    }
+    std::env::set_var("ALL_PROXY", &all_proxy);
+    std::env::set_var("HTTPS_PROXY", &https_proxy);
+
+    // Without a proxy set on the builder, ALL_PROXY is used for plain HTTP...
+    ApkMirror::builder()
+        .base_url("http://apkmirror.test")
+        .progress(SilentReporter)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(
+        all_server.await.unwrap(),
+        "GET http://apkmirror.test/ HTTP/1.1"
+    );
+
+    // ...and HTTPS_PROXY for HTTPS, which the stand-in does not tunnel
+    let result = ApkMirror::builder()
+        .base_url("https://apkmirror.test")
+        .progress(SilentReporter)
+        .build()
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        https_server.await.unwrap(),
+        "CONNECT apkmirror.test:443 HTTP/1.1"
+    );
+}